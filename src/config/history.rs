@@ -0,0 +1,233 @@
+use {
+    super::{get_config_dir, lock_config},
+    crate::{error::Error, fs},
+    std::{
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+const HISTORY_FILE_NAME: &str = "history.tsv";
+/// When the sum of all ranks goes over this value every rank is scaled down, and entries that
+/// were rarely visited are forgotten.
+const MAX_TOTAL_RANK: f64 = 10000.0;
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// A directory visited from a shell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    /// Absolute path of the directory.
+    pub path: String,
+    /// How often the directory was visited, decays as other directories are visited.
+    pub rank: f64,
+    /// Last time the directory was visited, in seconds since the Unix epoch.
+    pub last_accessed: u64,
+}
+
+/// Directories visited from the shells, used to rank directories by frecency: how frequently
+/// and how recently they were visited.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistoryEntry {
+    /// Rank weighted by how long ago the directory was last visited.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_accessed);
+        let factor = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * factor
+    }
+}
+
+impl History {
+    /// Serializes the history as lines of tab separated rank, last access time and path.
+    pub fn serialize(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|x| format!("{}\t{}\t{}", x.rank, x.last_accessed, x.path))
+            .collect()
+    }
+
+    /// Parses the lines written by `serialize`. Invalid lines are ignored, the history is only a
+    /// hint and is rewritten without them on the next visit.
+    pub fn deserialize(lines: Vec<String>) -> Self {
+        let entries = lines
+            .iter()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let rank = fields.next()?.parse::<f64>().ok()?;
+                let last_accessed = fields.next()?.parse::<u64>().ok()?;
+                let path = fields.next()?;
+                Some(HistoryEntry {
+                    path: path.to_string(),
+                    rank,
+                    last_accessed,
+                })
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Records a visit to the given directory. Paths with a line break can not be stored in a
+    /// line of the history file and are not recorded.
+    pub fn visit(&mut self, path: &str, now: u64) {
+        if path.contains(['\n', '\r']) {
+            return;
+        }
+        match self.entries.iter_mut().find(|x| x.path == path) {
+            Some(entry) => {
+                entry.rank += 1.0;
+                entry.last_accessed = now;
+            }
+            None => self.entries.push(HistoryEntry {
+                path: path.to_string(),
+                rank: 1.0,
+                last_accessed: now,
+            }),
+        }
+        let total_rank: f64 = self.entries.iter().map(|x| x.rank).sum();
+        if total_rank > MAX_TOTAL_RANK {
+            let factor = 0.9 * MAX_TOTAL_RANK / total_rank;
+            for entry in &mut self.entries {
+                entry.rank *= factor;
+            }
+            self.entries.retain(|x| x.rank >= 1.0);
+        }
+    }
+
+    /// Finds the directory with the highest frecency whose name contains `query`, ignoring case.
+    pub fn find(&self, query: &str, now: u64) -> Option<&HistoryEntry> {
+        let query = query.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|x| match Path::new(&x.path).file_name() {
+                Some(name) => name.to_string_lossy().to_lowercase().contains(&query),
+                None => false,
+            })
+            .max_by(|x, y| x.frecency(now).total_cmp(&y.frecency(now)))
+    }
+
+    /// Finds the directory with the highest frecency whose name is `query` or starts with it,
+    /// ignoring case.
+    pub fn find_by_prefix(&self, query: &str, now: u64) -> Option<&HistoryEntry> {
+        let query = query.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|x| match Path::new(&x.path).file_name() {
+                Some(name) => name.to_string_lossy().to_lowercase().starts_with(&query),
+                None => false,
+            })
+            .max_by(|x, y| x.frecency(now).total_cmp(&y.frecency(now)))
+    }
+
+    /// Finds the existing directory with the highest frecency named exactly `name`, used to find
+    /// where a deleted shortcut target was moved to.
+    pub fn find_by_name(&self, name: &str, now: u64) -> Option<&HistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|x| Path::new(&x.path).file_name().is_some_and(|x| x == name))
+            .filter(|x| Path::new(&x.path).is_dir())
+            .max_by(|x, y| x.frecency(now).total_cmp(&y.frecency(now)))
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn get_history_file() -> Result<PathBuf, Error> {
+    Ok(get_config_dir()?.join(HISTORY_FILE_NAME))
+}
+
+fn read_history(history_file: &Path) -> Result<History, Error> {
+    Ok(fs::read_lines(history_file)?
+        .map(History::deserialize)
+        .unwrap_or_default())
+}
+
+pub fn get_history() -> Result<History, Error> {
+    read_history(&get_history_file()?)
+}
+
+/// Records a visit to the given directory in the history file.
+pub fn record_visit(path: &Path) -> Result<(), Error> {
+    let history_file = get_history_file()?;
+    let _lock = lock_config(&history_file)?;
+    let mut history = read_history(&history_file)?;
+    history.visit(&path.to_string_lossy(), now());
+    fs::write_lines_atomic(&history_file, &history.serialize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_serialization() {
+        let mut history = History::default();
+        history.visit("/home/user/code", 100);
+        history.visit("/home/user/with\ttab", 200);
+        history.visit("/home/user/code", 300);
+        history.visit("/home/user/with\nnewline", 400);
+        let deserialized = History::deserialize(history.serialize());
+        assert_eq!(history, deserialized);
+        assert_eq!(2, deserialized.entries.len());
+        assert_eq!(2.0, deserialized.entries[0].rank);
+        assert_eq!(300, deserialized.entries[0].last_accessed);
+
+        // Lines that can not be parsed are skipped.
+        let mut lines = history.serialize();
+        lines.insert(1, "dir".to_string());
+        lines.push("1\tnot a time\t/home/user".to_string());
+        assert_eq!(history, History::deserialize(lines));
+    }
+
+    #[test]
+    fn test_history_find() {
+        let now = 10 * WEEK;
+        let mut history = History::default();
+        for _ in 0..10 {
+            history.visit("/home/user/old-project", now - 2 * WEEK);
+        }
+        for _ in 0..3 {
+            history.visit("/home/user/new-project", now - 10);
+        }
+        history.visit("/home/user/Downloads", now);
+        let found = history.find("PROJECT", now).expect("Should find a project");
+        assert_eq!("/home/user/new-project", found.path);
+        assert_eq!(None, history.find("music", now));
+    }
+
+    #[test]
+    fn test_history_aging() {
+        let mut history = History::default();
+        history.entries.push(HistoryEntry {
+            path: "/rare".to_string(),
+            rank: 1.0,
+            last_accessed: 0,
+        });
+        history.entries.push(HistoryEntry {
+            path: "/frequent".to_string(),
+            rank: MAX_TOTAL_RANK,
+            last_accessed: 0,
+        });
+        history.visit("/frequent", 1);
+        assert_eq!(1, history.entries.len());
+        assert!(history.entries[0].rank < MAX_TOTAL_RANK);
+    }
+}
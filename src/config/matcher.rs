@@ -0,0 +1,190 @@
+use {
+    super::{Config, Machine, ShortcutKV},
+    crate::error::Error,
+    std::path::Path,
+};
+
+/// How a query was matched to a shortcut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The query is exactly the shortcut key.
+    Exact,
+    /// The query is a prefix of a single shortcut key.
+    Prefix,
+    /// The query characters appear in order in the key or in the target directory name, with
+    /// the `fuzzy_score` of the best of them.
+    Fuzzy(i64),
+}
+
+const SCORE_MATCH: i64 = 1;
+const SCORE_CONSECUTIVE: i64 = 5;
+const SCORE_WORD_START: i64 = 8;
+const PENALTY_GAP: i64 = 1;
+
+impl Config {
+    /// Finds the shortcut for the given query: an exact key match first, then a unique key
+    /// prefix and finally the best fuzzy match against the keys and target directory names.
+    /// Returns `Error::AmbiguousShortcut` instead of guessing when several shortcuts are equally
+    /// good matches. Only the shortcuts used on the current machine are considered.
+    pub fn find(&self, query: &str) -> Result<Option<(&ShortcutKV, MatchKind)>, Error> {
+        let shortcuts = self.active_shortcuts(&Machine::current());
+        if let Some(shortcut) = shortcuts.iter().find(|x| x.key == query) {
+            return Ok(Some((shortcut, MatchKind::Exact)));
+        }
+        if query.is_empty() {
+            return Ok(None);
+        }
+
+        let query_lowercase = query.to_lowercase();
+        let prefixed: Vec<&ShortcutKV> = shortcuts
+            .iter()
+            .filter(|x| x.key.to_lowercase().starts_with(&query_lowercase))
+            .copied()
+            .collect();
+        match prefixed.len() {
+            0 => (),
+            1 => return Ok(Some((prefixed[0], MatchKind::Prefix))),
+            _ => return Err(ambiguous(query, prefixed)),
+        }
+
+        let mut best_score: Option<i64> = None;
+        let mut best: Vec<&ShortcutKV> = vec![];
+        for shortcut in shortcuts {
+            let key_score = fuzzy_score(query, &shortcut.key);
+            let name_score = Path::new(&shortcut.value)
+                .file_name()
+                .and_then(|name| fuzzy_score(query, &name.to_string_lossy()));
+            let score = match key_score.max(name_score) {
+                Some(score) => score,
+                None => continue,
+            };
+            match best_score {
+                Some(best_score) if best_score > score => (),
+                Some(best_score) if best_score == score => best.push(shortcut),
+                _ => {
+                    best_score = Some(score);
+                    best = vec![shortcut];
+                }
+            }
+        }
+        match (best.len(), best_score) {
+            (1, Some(score)) => Ok(Some((best[0], MatchKind::Fuzzy(score)))),
+            (0, _) | (_, None) => Ok(None),
+            _ => Err(ambiguous(query, best)),
+        }
+    }
+}
+
+fn ambiguous(query: &str, candidates: Vec<&ShortcutKV>) -> Error {
+    Error::AmbiguousShortcut {
+        query: query.to_string(),
+        candidates: candidates
+            .into_iter()
+            .map(|x| (x.key.clone(), x.value.clone()))
+            .collect(),
+    }
+}
+
+/// Scores how well `query` matches `candidate`, ignoring case. Returns `None` if the query
+/// characters do not appear in order in the candidate. Consecutive characters and characters at
+/// the start of a word score higher, characters skipped between matches lower the score.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut last_match: Option<usize> = None;
+    for query_char in query.to_lowercase().chars() {
+        let offset = candidate[position..]
+            .iter()
+            .position(|&x| x == query_char)?;
+        let index = position + offset;
+        score += SCORE_MATCH;
+        if index == 0 || is_word_separator(candidate[index - 1]) {
+            score += SCORE_WORD_START;
+        }
+        match last_match {
+            Some(last) if last + 1 == index => score += SCORE_CONSECUTIVE,
+            Some(last) => score -= PENALTY_GAP * (index - last - 1) as i64,
+            None => (),
+        }
+        last_match = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+fn is_word_separator(c: char) -> bool {
+    matches!(c, '-' | '_' | '.' | ' ' | '/' | '\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(shortcuts: &[(&str, &str)]) -> Config {
+        Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: shortcuts
+                .iter()
+                .map(|(key, value)| ShortcutKV::new(key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    fn find_key(config: &Config, query: &str) -> Option<(String, MatchKind)> {
+        config
+            .find(query)
+            .expect("Match should not be ambiguous")
+            .map(|(shortcut, kind)| (shortcut.key.clone(), kind))
+    }
+
+    #[test]
+    fn test_find() {
+        let config = config(&[
+            ("repo", "/home/user/code/repositories"),
+            ("re", "/home/user/recordings"),
+            ("dl", "/home/user/Downloads"),
+            ("docs", "/home/user/Documents"),
+            ("nvim", "/home/user/.config/nvim"),
+        ]);
+        assert_eq!(
+            Some(("re".to_string(), MatchKind::Exact)),
+            find_key(&config, "re")
+        );
+        assert_eq!(
+            Some(("repo".to_string(), MatchKind::Prefix)),
+            find_key(&config, "rep")
+        );
+        assert_eq!(
+            Some(("dl".to_string(), MatchKind::Fuzzy(33))),
+            find_key(&config, "downl")
+        );
+        assert_eq!(
+            Some(("nvim".to_string(), MatchKind::Fuzzy(15))),
+            find_key(&config, "nvm")
+        );
+        assert_eq!(None, find_key(&config, "xyz"));
+        assert_eq!(None, find_key(&config, ""));
+    }
+
+    #[test]
+    fn test_find_ambiguous() {
+        let config = config(&[
+            ("work-api", "/home/user/api"),
+            ("work-web", "/home/user/web"),
+        ]);
+        match config.find("wor") {
+            Err(Error::AmbiguousShortcut { candidates, .. }) => assert_eq!(2, candidates.len()),
+            other => panic!("Expected ambiguous match, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(None, fuzzy_score("abc", "acb"));
+        assert!(fuzzy_score("dl", "downloads") < fuzzy_score("dl", "d-l"));
+        assert!(fuzzy_score("doc", "my-documents") > fuzzy_score("doc", "adobe-cache"));
+    }
+}
@@ -1,6 +1,7 @@
 use {
-    crate::fs,
+    crate::{error::Error, fs},
     directories::ProjectDirs,
+    serde::{Deserialize, Serialize},
     std::{
         fmt,
         path::{Path, PathBuf},
@@ -8,26 +9,79 @@ use {
     },
 };
 
-#[derive(Debug, PartialEq, Eq)]
+pub mod history;
+mod matcher;
+pub mod project;
+mod scope;
+
+pub use {
+    matcher::{MatchKind, fuzzy_score},
+    scope::{Machine, Scope},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ConfigVersion {
+    /// 0.1.0: Plain text, the version, path location and command in the first three lines,
+    /// followed by alternating shortcut key and value lines.
     V0,
+    /// 1.0.0: TOML document with a `[[shortcut]]` table per shortcut.
+    V1,
 }
 
 /// Running `$ {command} {key}` will be equivalent to doing `$ cd {value}`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ShortcutKV {
     /// Key for the shortcut.
     pub key: String,
-    /// Absoute path of the target directory.
+    /// Absoute path of the target directory, or with `literal` the path as typed.
     pub value: String,
+    /// Machines the shortcut is used on, stored as optional `host` and `os` fields.
+    #[serde(flatten)]
+    pub scope: Scope,
+    /// The value was stored as typed, its `~` and environment variables are expanded every time
+    /// it is used, see `fs::expand_path`. Only stored when true.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub literal: bool,
+}
+
+impl ShortcutKV {
+    /// Creates a shortcut used on every machine.
+    pub fn new(key: String, value: String) -> Self {
+        ShortcutKV {
+            key,
+            value,
+            scope: Scope::default(),
+            literal: false,
+        }
+    }
+
+    /// Target directory, with `~` and environment variables in `value` expanded if it is
+    /// `literal`.
+    pub fn target(&self) -> Result<PathBuf, Error> {
+        if self.literal {
+            fs::expand_path(&self.value)
+        } else {
+            Ok(PathBuf::from(&self.value))
+        }
+    }
+}
+
+impl fmt::Display for ShortcutKV {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> {}", self.key, self.value)?;
+        if !self.scope.is_global() {
+            write!(f, " ({})", self.scope)?;
+        }
+        Ok(())
+    }
 }
 
 /// Data persisted in the config file.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Config {
     /// Version of config, needed for correct deserialization.
-    /// For serialization, the latest version will always be used.
-    /// Currently 0.1.0 it the only valid version.
+    /// For serialization, the latest version will always be used, so older config files are
+    /// migrated the next time they are written.
     pub version: ConfigVersion,
     /// Directory in PATH in which the command executable is located.
     pub path_location: Option<String>,
@@ -44,6 +98,17 @@ pub enum ConfigAddResult {
     NoChange,
 }
 
+/// What to do when merging a shortcut whose key is already used for a different target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing shortcut.
+    Skip,
+    /// Replace the target of the existing shortcut.
+    Overwrite,
+    /// Add the shortcut with a different key.
+    Rename,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ConfigRemoveResult {
     Removed(ShortcutKV),
@@ -53,72 +118,113 @@ pub enum ConfigRemoveResult {
 const ORGANIZATION: &str = "niquefaDiego";
 const APPLICATION: &str = "Shortcuts";
 const CONFIG_FILE_NAME: &str = "shortcuts.config";
+const LOCK_FILE_EXTENSION: &str = ".lock";
+/// Command used to change directory when setup is not given one.
+pub const DEFAULT_COMMAND: &str = "s";
 
 impl Config {
     pub fn latest() -> ConfigVersion {
-        ConfigVersion::V0
+        ConfigVersion::V1
     }
 }
 
 impl fmt::Display for ConfigVersion {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "0.1.0")
+        match self {
+            ConfigVersion::V0 => write!(f, "0.1.0"),
+            ConfigVersion::V1 => write!(f, "1.0.0"),
+        }
     }
 }
 
 impl FromStr for ConfigVersion {
-    type Err = String;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "0.1.0" => Ok(ConfigVersion::V0),
+            "1.0.0" => Ok(ConfigVersion::V1),
             _ => {
                 let msg = format!("Invalid config version \"{}\".", s);
-                Err(msg.to_string())
+                Err(Error::InvalidConfig(msg))
             }
         }
     }
 }
 
+/// Layout of the config file since version 1.0.0, see `ConfigVersion::V1`.
+#[derive(Serialize, Deserialize)]
+struct ConfigFileV1 {
+    version: String,
+    command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path_location: Option<String>,
+    #[serde(default, rename = "shortcut")]
+    shortcuts: Vec<ShortcutKV>,
+}
+
 impl Config {
+    /// Serializes the config using the latest version.
     pub fn serialize(&self) -> Vec<String> {
-        let mut ans: Vec<String> = Vec::new();
-        ans.reserve_exact(self.shortcuts.len() * 2 + 3);
-        ans.push(self.version.to_string());
-        ans.push(match &self.path_location {
-            Some(dir) => dir.clone(),
-            None => "".to_string(),
-        });
-        ans.push(self.command.clone());
-        for shortcut in &self.shortcuts {
-            ans.push(shortcut.key.clone());
-            ans.push(shortcut.value.clone());
+        let file = ConfigFileV1 {
+            version: Config::latest().to_string(),
+            command: self.command.clone(),
+            path_location: self.path_location.clone(),
+            shortcuts: self.shortcuts.clone(),
+        };
+        let content = toml::to_string(&file)
+            .expect("Config only contains strings and booleans, it must serialize");
+        content.lines().map(String::from).collect()
+    }
+
+    /// Deserializes a config of any version, `Config::version` is set to the version read.
+    pub fn deserialize(lines: Vec<String>) -> Result<Self, Error> {
+        // Version 0.1.0 files start with the bare version line, which is not valid TOML.
+        match lines.first() {
+            Some(first_line) if first_line.trim() == ConfigVersion::V0.to_string() => {
+                Self::deserialize_v0(lines)
+            }
+            _ => Self::deserialize_v1(&lines.join("\n")),
         }
-        ans
     }
 
-    pub fn deserialize(lines: Vec<String>) -> Result<Self, String> {
+    fn deserialize_v1(content: &str) -> Result<Self, Error> {
+        let file: ConfigFileV1 = match toml::from_str(content) {
+            Ok(file) => file,
+            Err(err) => return Err(Error::InvalidConfig(format!("Invalid TOML: {}", err))),
+        };
+        let version = ConfigVersion::from_str(&file.version)?;
+        if version != ConfigVersion::V1 {
+            let msg = format!("Config version \"{}\" is not stored as TOML", version);
+            return Err(Error::InvalidConfig(msg));
+        }
+        Ok(Self {
+            version,
+            path_location: file.path_location,
+            command: file.command,
+            shortcuts: file.shortcuts,
+        })
+    }
+
+    fn deserialize_v0(lines: Vec<String>) -> Result<Self, Error> {
         const HEADER_LINES: usize = 3;
         if lines.len() < HEADER_LINES {
             let msg = format!("Config must contain at least {} lines", HEADER_LINES);
-            return Err(msg);
+            return Err(Error::InvalidConfig(msg));
         }
-        if (lines.len() - HEADER_LINES) % 2 != 0 {
-            return Err("Config file has an invalid number of lines".to_string());
+        if !(lines.len() - HEADER_LINES).is_multiple_of(2) {
+            let msg = "Config file has an invalid number of lines".to_string();
+            return Err(Error::InvalidConfig(msg));
         }
         let version = ConfigVersion::from_str(&lines[0])?;
-        let path_location: Option<String> = if lines[1].len() == 0 {
+        let path_location: Option<String> = if lines[1].is_empty() {
             None
         } else {
             Some(lines[1].clone())
         };
         let command = lines[2].clone();
-        let mut shortcuts: Vec<ShortcutKV> = vec![];
-        shortcuts.reserve((lines.len() - HEADER_LINES) / 2);
+        let mut shortcuts: Vec<ShortcutKV> = Vec::with_capacity((lines.len() - HEADER_LINES) / 2);
         for i in (HEADER_LINES..lines.len()).step_by(2) {
-            shortcuts.push(ShortcutKV {
-                key: lines[i].clone(),
-                value: lines[i + 1].clone(),
-            });
+            shortcuts.push(ShortcutKV::new(lines[i].clone(), lines[i + 1].clone()));
         }
         Ok(Self {
             version,
@@ -128,28 +234,80 @@ impl Config {
         })
     }
 
-    pub fn add(&mut self, key: String, value: String) -> Result<ConfigAddResult, String> {
-        match self.shortcuts.iter().position(|x| x.key == key) {
+    /// Adds the shortcut, replacing the target of the shortcut with the same key and scope.
+    pub fn add(&mut self, shortcut: ShortcutKV) -> Result<ConfigAddResult, Error> {
+        let position = self
+            .shortcuts
+            .iter()
+            .position(|x| x.key == shortcut.key && x.scope == shortcut.scope);
+        match position {
             Some(position) => {
-                if value == self.shortcuts[position].value {
+                if shortcut == self.shortcuts[position] {
                     return Ok(ConfigAddResult::NoChange);
                 }
                 let existing = self.shortcuts[position].clone();
-                self.shortcuts[position].value = value;
+                self.shortcuts[position] = shortcut;
                 let updated = self.shortcuts[position].clone();
-                return Ok(ConfigAddResult::Updated(existing, updated));
+                Ok(ConfigAddResult::Updated(existing, updated))
             }
             None => {
-                let new_shortcut = ShortcutKV { key, value };
-                self.shortcuts.push(new_shortcut.clone());
+                self.shortcuts.push(shortcut.clone());
                 self.shortcuts.sort_by(|a, b| a.key.cmp(&b.key));
-                Ok(ConfigAddResult::Created(new_shortcut))
+                Ok(ConfigAddResult::Created(shortcut))
             }
         }
     }
 
-    pub fn remove(&mut self, key: String) -> Result<ConfigRemoveResult, String> {
-        match self.shortcuts.iter().position(|x| x.key == key) {
+    /// Adds the shortcut, using `policy` if its key is already used for a different target in
+    /// the same scope. When renaming, the first key of `{key}2`, `{key}3`, ... not used in the
+    /// scope is used.
+    pub fn merge(
+        &mut self,
+        shortcut: ShortcutKV,
+        policy: ConflictPolicy,
+    ) -> Result<ConfigAddResult, Error> {
+        let conflict = self.shortcuts.iter().any(|x| {
+            x.key == shortcut.key && x.scope == shortcut.scope && x.value != shortcut.value
+        });
+        match policy {
+            _ if !conflict => self.add(shortcut),
+            ConflictPolicy::Skip => Ok(ConfigAddResult::NoChange),
+            ConflictPolicy::Overwrite => self.add(shortcut),
+            ConflictPolicy::Rename => {
+                // Merging the same shortcut again must not add it once more, a copy renamed
+                // before has a `{key}N` key and the same target and scope.
+                let is_renamed_key = |key: &str| {
+                    key.strip_prefix(&shortcut.key)
+                        .and_then(|x| x.parse::<u32>().ok())
+                        .is_some_and(|x| x >= 2)
+                };
+                let renamed_before = self.shortcuts.iter().any(|x| {
+                    x.value == shortcut.value && x.scope == shortcut.scope && is_renamed_key(&x.key)
+                });
+                if renamed_before {
+                    return Ok(ConfigAddResult::NoChange);
+                }
+                let key = (2..)
+                    .map(|i| format!("{}{}", shortcut.key, i))
+                    .find(|key| {
+                        !self
+                            .shortcuts
+                            .iter()
+                            .any(|x| x.key == *key && x.scope == shortcut.scope)
+                    })
+                    .expect("There are finitely many shortcuts");
+                self.add(ShortcutKV { key, ..shortcut })
+            }
+        }
+    }
+
+    /// Removes the shortcut with the given key and scope.
+    pub fn remove(&mut self, key: String, scope: &Scope) -> Result<ConfigRemoveResult, Error> {
+        match self
+            .shortcuts
+            .iter()
+            .position(|x| x.key == key && x.scope == *scope)
+        {
             None => Ok(ConfigRemoveResult::NotFound),
             Some(position) => {
                 let removed_value = self.shortcuts[position].clone();
@@ -160,35 +318,34 @@ impl Config {
     }
 }
 
-fn get_project_dirs() -> Result<ProjectDirs, String> {
+fn get_project_dirs() -> Result<ProjectDirs, Error> {
     match ProjectDirs::from("", ORGANIZATION, APPLICATION) {
         Some(proj_dirs) => Ok(proj_dirs),
-        None => Err(
-            "No valid home directory path could be retrieved from the operating system."
-                .to_string(),
-        ),
+        None => Err(Error::NoHomeDirectory),
     }
 }
 
-fn get_config_file() -> Result<PathBuf, String> {
+fn get_config_dir() -> Result<PathBuf, Error> {
     let proj_dirs = get_project_dirs()?;
     let dir = proj_dirs.config_local_dir();
     fs::ensure_dir(dir)?;
-    Ok(PathBuf::from(dir).join(CONFIG_FILE_NAME))
+    Ok(PathBuf::from(dir))
 }
 
-fn read_config(config_file: &Path) -> Result<Option<Config>, String> {
+fn get_config_file() -> Result<PathBuf, Error> {
+    Ok(get_config_dir()?.join(CONFIG_FILE_NAME))
+}
+
+fn read_config(config_file: &Path) -> Result<Option<Config>, Error> {
     match fs::read_lines(config_file)? {
         Some(content) => {
             let config = match Config::deserialize(content) {
                 Ok(config) => config,
                 Err(err) => {
-                    let msg = format!(
-                        "Corrupted config file: \"{}\".\n{}",
-                        config_file.display(),
-                        err
-                    );
-                    return Err(msg.to_string());
+                    return Err(Error::CorruptedConfig {
+                        path: config_file.to_path_buf(),
+                        reason: err.to_string(),
+                    });
                 }
             };
             Ok(Some(config))
@@ -197,58 +354,131 @@ fn read_config(config_file: &Path) -> Result<Option<Config>, String> {
     }
 }
 
-fn get_config_from_file(config_file: &Path) -> Result<Config, String> {
+fn get_config_from_file(config_file: &Path) -> Result<Config, Error> {
     match read_config(config_file) {
         Ok(Some(config)) => Ok(config),
-        Ok(None) => Err("Config file not found. Run one-time setup (see --help)".to_string()),
+        Ok(None) => Err(Error::ConfigNotFound),
         Err(err) => Err(err),
     }
 }
 
-pub fn get_config() -> Result<Config, String> {
+pub fn get_config() -> Result<Config, Error> {
     let config_file = get_config_file()?;
     get_config_from_file(&config_file)
 }
 
-pub fn add_shortcut(key: &str, path: &Path) -> Result<ConfigAddResult, String> {
+/// Acquires an exclusive advisory lock for modifying the config file, the lock is released when
+/// the returned file is dropped.
+fn lock_config(config_file: &Path) -> Result<std::fs::File, Error> {
+    fs::lock_file(&get_lock_file(config_file))
+}
+
+fn get_lock_file(config_file: &Path) -> PathBuf {
+    let mut lock_file = config_file.as_os_str().to_owned();
+    lock_file.push(LOCK_FILE_EXTENSION);
+    PathBuf::from(lock_file)
+}
+
+fn write_config(config_file: &Path, config: &Config) -> Result<(), Error> {
+    let serialized_config = config.serialize();
+    fs::write_lines_atomic(config_file, &serialized_config)
+}
+
+/// Adds the shortcut, its value is either an absolute path or a path to be expanded when used.
+/// If the shortcut is used on this machine the expanded path must be an existing directory.
+pub fn add_shortcut(shortcut: ShortcutKV) -> Result<ConfigAddResult, Error> {
     let config_file = get_config_file()?;
-    let mut config = get_config_from_file(&config_file)?;
-    if !path.is_dir() {
-        return Err(format!("\"{}\" is not a directory", path.display()).to_string());
+    add_shortcut_to_file(&config_file, shortcut)
+}
+
+fn add_shortcut_to_file(
+    config_file: &Path,
+    shortcut: ShortcutKV,
+) -> Result<ConfigAddResult, Error> {
+    let _lock = lock_config(config_file)?;
+    let mut config = get_config_from_file(config_file)?;
+    if shortcut.scope.applies_to(&Machine::current()) {
+        let path = shortcut.target()?;
+        if !path.is_dir() {
+            return Err(Error::NotADirectory(path));
+        }
     }
-    let path = path.to_string_lossy();
-    let add_result = config.add(key.to_string(), path.to_string())?;
+    let add_result = config.add(shortcut)?;
     if add_result != ConfigAddResult::NoChange {
-        let serialized_config = config.serialize();
-        fs::write_lines(&config_file, &serialized_config)?;
+        write_config(config_file, &config)?;
     }
     Ok(add_result)
 }
 
-pub fn remove_shortcut(key: &str) -> Result<ConfigRemoveResult, String> {
+/// Merges all the given shortcuts into the config file, writing it once.
+pub fn merge_shortcuts(
+    shortcuts: &[ShortcutKV],
+    policy: ConflictPolicy,
+) -> Result<Vec<ConfigAddResult>, Error> {
     let config_file = get_config_file()?;
+    let _lock = lock_config(&config_file)?;
     let mut config = get_config_from_file(&config_file)?;
-    let remove_result = config.remove(key.to_string())?;
+    let mut add_results = Vec::with_capacity(shortcuts.len());
+    for shortcut in shortcuts {
+        add_results.push(config.merge(shortcut.clone(), policy)?);
+    }
+    if add_results.iter().any(|x| *x != ConfigAddResult::NoChange) {
+        write_config(&config_file, &config)?;
+    }
+    Ok(add_results)
+}
+
+pub fn remove_shortcut(key: &str, scope: &Scope) -> Result<ConfigRemoveResult, Error> {
+    let config_file = get_config_file()?;
+    let _lock = lock_config(&config_file)?;
+    let mut config = get_config_from_file(&config_file)?;
+    let remove_result = config.remove(key.to_string(), scope)?;
     if remove_result != ConfigRemoveResult::NotFound {
-        let serialized_config = config.serialize();
-        fs::write_lines(&config_file, &serialized_config)?;
+        write_config(&config_file, &config)?;
     }
     Ok(remove_result)
 }
 
-pub fn create_config(command: &str, path_location: Option<PathBuf>) -> Result<Config, String> {
+/// Deletes the config file and the directory history, returning the config file location if it
+/// existed.
+pub fn delete_config() -> Result<Option<PathBuf>, Error> {
+    let config_file = get_config_file()?;
+    let history_file = history::get_history_file()?;
+    fs::remove_file(&get_lock_file(&history_file))?;
+    fs::remove_file(&history_file)?;
+    fs::remove_file(&get_lock_file(&config_file))?;
+    if fs::remove_file(&config_file)? {
+        Ok(Some(config_file))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Creates the config file for the given command, keeping the shortcuts of an existing one.
+pub fn create_config(command: &str, path_location: Option<PathBuf>) -> Result<Config, Error> {
+    let config_file = get_config_file()?;
+    let _lock = lock_config(&config_file)?;
+    let config = get_setup_config(command, path_location)?;
+    write_config(&config_file, &config)?;
+    Ok(config)
+}
+
+/// The config `create_config` would write, without writing it.
+pub fn get_setup_config(command: &str, path_location: Option<PathBuf>) -> Result<Config, Error> {
     let path_location: Option<String> = match path_location {
         Some(dir) => {
             let absolute_dir = fs::to_absolute_path(&dir)?;
             if !absolute_dir.is_dir() {
-                let msg = format!("'{}' is not a valid directory", dir.display());
-                return Err(msg);
+                return Err(Error::NotADirectory(dir));
             }
             Some(absolute_dir.to_string_lossy().to_string())
         }
         None => None,
     };
-    let config_file = get_config_file()?;
+    // Not `get_config_file`, which creates the config directory.
+    let config_file = get_project_dirs()?
+        .config_local_dir()
+        .join(CONFIG_FILE_NAME);
     let config = match read_config(&config_file)? {
         Some(config) => {
             let mut config = config;
@@ -257,13 +487,11 @@ pub fn create_config(command: &str, path_location: Option<PathBuf>) -> Result<Co
         }
         None => Config {
             version: Config::latest(),
-            path_location: path_location,
+            path_location,
             command: command.to_string(),
             shortcuts: vec![],
         },
     };
-    let serialized_config = config.serialize();
-    fs::write_lines(&config_file, &serialized_config)?;
     Ok(config)
 }
 
@@ -280,53 +508,237 @@ mod tests {
     #[test]
     fn test_config_serialization() {
         run_test(Config {
-            version: ConfigVersion::V0,
+            version: ConfigVersion::V1,
             path_location: Some("C:\\Path".to_string()),
             command: "cd2".to_string(),
             shortcuts: vec![],
         });
 
         run_test(Config {
-            version: ConfigVersion::V0,
+            version: ConfigVersion::V1,
             path_location: Some("C:\\Path".to_string()),
             command: "changedir".to_string(),
             shortcuts: vec![
-                ShortcutKV {
-                    key: "dls".to_string(),
-                    value: "C:\\Users\\user\\Downloads".to_string(),
-                },
-                ShortcutKV {
-                    key: "x84".to_string(),
-                    value: "C:\\Program Files (x84)".to_string(),
-                },
+                ShortcutKV::new("dls".to_string(), "C:\\Users\\user\\Downloads".to_string()),
+                ShortcutKV::new("x84".to_string(), "C:\\Program Files (x84)".to_string()),
             ],
         });
 
         run_test(Config {
-            version: ConfigVersion::V0,
+            version: ConfigVersion::V1,
             path_location: None,
             command: "s".to_string(),
             shortcuts: vec![
+                ShortcutKV::new(
+                    "pics".to_string(),
+                    "/home/users/spiderman/Pictures".to_string(),
+                ),
+                ShortcutKV::new(
+                    "src".to_string(),
+                    "/home/users/spiderman/GitHub".to_string(),
+                ),
+                ShortcutKV::new(
+                    "nvim".to_string(),
+                    "/home/users/spiderman/.config/nvim".to_string(),
+                ),
+            ],
+        });
+
+        run_test(Config {
+            version: ConfigVersion::V1,
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![],
+        });
+    }
+
+    #[test]
+    fn test_merge() {
+        let shortcut = |key: &str, value: &str| ShortcutKV::new(key.to_string(), value.to_string());
+        let mut config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![shortcut("dl", "/home/user/Downloads")],
+        };
+        let same = shortcut("dl", "/home/user/Downloads");
+        let conflict = shortcut("dl", "/mnt/downloads");
+        for policy in [
+            ConflictPolicy::Skip,
+            ConflictPolicy::Overwrite,
+            ConflictPolicy::Rename,
+        ] {
+            let result = config.merge(same.clone(), policy);
+            assert_eq!(ConfigAddResult::NoChange, result.unwrap());
+        }
+        let result = config.merge(conflict.clone(), ConflictPolicy::Skip);
+        assert_eq!(ConfigAddResult::NoChange, result.unwrap());
+        let result = config.merge(conflict.clone(), ConflictPolicy::Rename);
+        let renamed = shortcut("dl2", "/mnt/downloads");
+        assert_eq!(ConfigAddResult::Created(renamed), result.unwrap());
+        let result = config.merge(conflict.clone(), ConflictPolicy::Rename);
+        assert_eq!(ConfigAddResult::NoChange, result.unwrap());
+        let result = config.merge(conflict.clone(), ConflictPolicy::Overwrite);
+        assert_eq!(
+            ConfigAddResult::Updated(same, conflict.clone()),
+            result.unwrap()
+        );
+
+        // An unrelated key with the same target, or a renamed copy in another scope, is not the
+        // renamed copy of the shortcut.
+        let mut config = Config {
+            shortcuts: vec![
+                shortcut("dl", "/home/user/Downloads"),
+                shortcut("dlx", "/mnt/downloads"),
                 ShortcutKV {
-                    key: "pics".to_string(),
-                    value: "/home/users/spiderman/Pictures".to_string(),
-                },
-                ShortcutKV {
-                    key: "src".to_string(),
-                    value: "/home/users/spiderman/GitHub".to_string(),
-                },
-                ShortcutKV {
-                    key: "nvim".to_string(),
-                    value: "/home/users/spiderman/.config/nvim".to_string(),
+                    scope: Scope {
+                        host: Some("laptop".to_string()),
+                        os: None,
+                    },
+                    ..shortcut("dl2", "/mnt/downloads")
                 },
             ],
+            ..config
+        };
+        let result = config.merge(conflict.clone(), ConflictPolicy::Rename);
+        let renamed = shortcut("dl2", "/mnt/downloads");
+        assert_eq!(ConfigAddResult::Created(renamed), result.unwrap());
+        let result = config.merge(shortcut("dl", "/media"), ConflictPolicy::Rename);
+        assert_eq!(
+            ConfigAddResult::Created(shortcut("dl3", "/media")),
+            result.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_concurrent_add_shortcut() {
+        const THREADS: usize = 8;
+        const ADDS_PER_THREAD: usize = 16;
+        let dir = std::env::temp_dir().join(format!("shortcut-test-{}", std::process::id()));
+        fs::ensure_dir(&dir).expect("Temp dir should be created");
+        let config_file = dir.join(CONFIG_FILE_NAME);
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![],
+        };
+        write_config(&config_file, &config).expect("Config should be written");
+
+        std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let config_file = &config_file;
+                let dir = &dir;
+                scope.spawn(move || {
+                    for i in 0..ADDS_PER_THREAD {
+                        let key = format!("key-{}-{}", thread, i);
+                        let shortcut = ShortcutKV::new(key, dir.to_string_lossy().into_owned());
+                        add_shortcut_to_file(config_file, shortcut)
+                            .expect("Concurrent add should work");
+                    }
+                });
+            }
         });
 
+        let config = get_config_from_file(&config_file).expect("Config should not be corrupted");
+        assert_eq!(THREADS * ADDS_PER_THREAD, config.shortcuts.len());
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
+
+    #[test]
+    fn test_config_v0_migration() {
+        let lines: Vec<String> = vec![
+            "0.1.0",
+            "",
+            "s",
+            "dls",
+            "/home/users/spiderman/Downloads",
+            "x84",
+            "C:\\Program Files (x84)",
+        ]
+        .into_iter()
+        .map(|x| x.to_string())
+        .collect();
+        let mut config = Config::deserialize(lines).expect("V0 deserialization should work");
+        assert_eq!(ConfigVersion::V0, config.version);
+        assert_eq!(None, config.path_location);
+        assert_eq!("s", config.command);
+        assert_eq!(2, config.shortcuts.len());
+        assert_eq!("C:\\Program Files (x84)", config.shortcuts[1].value);
+
+        let migrated = Config::deserialize(config.serialize()).expect("V1 should deserialize");
+        assert_eq!(ConfigVersion::V1, migrated.version);
+        config.version = ConfigVersion::V1;
+        assert_eq!(config, migrated);
+    }
+
+    #[test]
+    fn test_config_value_with_newline() {
         run_test(Config {
-            version: ConfigVersion::V0,
+            version: ConfigVersion::V1,
             path_location: None,
             command: "s".to_string(),
-            shortcuts: vec![],
+            shortcuts: vec![ShortcutKV::new(
+                "odd".to_string(),
+                "/tmp/line\nbreak".to_string(),
+            )],
         });
     }
+
+    #[test]
+    fn test_config_scoped_shortcuts() {
+        let config = Config {
+            version: ConfigVersion::V1,
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![
+                ShortcutKV::new("repo".to_string(), "~/code".to_string()),
+                ShortcutKV {
+                    key: "repo".to_string(),
+                    value: "D:\\code".to_string(),
+                    scope: Scope {
+                        host: Some("desktop".to_string()),
+                        os: Some("windows".to_string()),
+                    },
+                    literal: false,
+                },
+            ],
+        };
+        let serialized = config.serialize();
+        assert!(serialized.contains(&"host = \"desktop\"".to_string()));
+        assert!(serialized.contains(&"os = \"windows\"".to_string()));
+        run_test(config);
+    }
+
+    #[test]
+    fn test_literal_target() {
+        let dir = std::env::temp_dir().join(format!("shortcut-literal-{}", std::process::id()));
+        let dollar_dir = dir.join("$HOME");
+        fs::ensure_dir(&dollar_dir).expect("Temp dir should be created");
+        let value = dollar_dir.to_string_lossy().into_owned();
+
+        // Only literal targets are expanded, a directory named `$HOME` is kept as it is.
+        let absolute = ShortcutKV::new("rv".to_string(), value.clone());
+        assert_eq!(dollar_dir, absolute.target().unwrap());
+        assert!(absolute.target().unwrap().is_dir());
+        let literal = ShortcutKV {
+            literal: true,
+            ..ShortcutKV::new("home".to_string(), "$HOME".to_string())
+        };
+        assert_ne!(PathBuf::from("$HOME"), literal.target().unwrap());
+
+        let config = Config {
+            version: ConfigVersion::V1,
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![literal, absolute],
+        };
+        let serialized = config.serialize();
+        assert_eq!(
+            1,
+            serialized.iter().filter(|x| *x == "literal = true").count()
+        );
+        run_test(config);
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
 }
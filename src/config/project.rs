@@ -0,0 +1,162 @@
+use {
+    super::{Config, ShortcutKV},
+    crate::{error::Error, fs},
+    colored::Colorize,
+    serde::Deserialize,
+    std::path::{Path, PathBuf},
+};
+
+/// Name of the file with the shortcuts of a project, usually committed to its repository.
+pub const PROJECT_FILE_NAME: &str = ".shortcuts";
+
+/// Shortcuts read from a project file, with their targets resolved relative to the directory of
+/// the file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectShortcuts {
+    pub file: PathBuf,
+    pub shortcuts: Vec<ShortcutKV>,
+}
+
+/// Layout of the project file, the same `[[shortcut]]` tables of the config file.
+#[derive(Deserialize)]
+struct ProjectFile {
+    #[serde(default, rename = "shortcut")]
+    shortcuts: Vec<ShortcutKV>,
+}
+
+impl ProjectShortcuts {
+    /// Parses the content of the project file at `file`.
+    pub fn deserialize(file: &Path, content: &str) -> Result<Self, Error> {
+        let project_file: ProjectFile = match toml::from_str(content) {
+            Ok(project_file) => project_file,
+            Err(err) => {
+                return Err(Error::CorruptedConfig {
+                    path: file.to_path_buf(),
+                    reason: format!("Invalid TOML: {}", err),
+                });
+            }
+        };
+        let dir = file.parent().unwrap_or(Path::new(""));
+        let mut shortcuts = Vec::with_capacity(project_file.shortcuts.len());
+        for mut shortcut in project_file.shortcuts {
+            // Project files are shared, their targets are always written as typed.
+            let target = dir.join(fs::expand_path(&shortcut.value)?);
+            shortcut.value = target.to_string_lossy().into_owned();
+            shortcut.literal = false;
+            shortcuts.push(shortcut);
+        }
+        Ok(ProjectShortcuts {
+            file: file.to_path_buf(),
+            shortcuts,
+        })
+    }
+}
+
+impl Config {
+    /// Layers the project shortcuts over the config: a project shortcut hides every shortcut of
+    /// the config with the same key.
+    pub fn with_project(mut self, project: &ProjectShortcuts) -> Config {
+        self.shortcuts
+            .retain(|x| project.shortcuts.iter().all(|y| y.key != x.key));
+        self.shortcuts.extend(project.shortcuts.iter().cloned());
+        self.shortcuts.sort_by(|a, b| a.key.cmp(&b.key));
+        self
+    }
+}
+
+/// Finds the nearest project file in `dir` or any of its ancestors.
+pub fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|x| x.join(PROJECT_FILE_NAME))
+        .find(|x| x.is_file())
+}
+
+/// Reads the shortcuts of the nearest project file from the current directory, if any.
+pub fn get_project_shortcuts() -> Result<Option<ProjectShortcuts>, Error> {
+    let current_dir = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(err) => return Err(Error::io("getting current directory", ".", err)),
+    };
+    Ok(find_project_shortcuts(&current_dir))
+}
+
+/// Reads the shortcuts of the nearest project file from `dir`, if any. A project file that can not
+/// be read is skipped with a warning, so a broken file in some repository does not break the
+/// command in every directory below it.
+pub fn find_project_shortcuts(dir: &Path) -> Option<ProjectShortcuts> {
+    let file = find_project_file(dir)?;
+    let project = fs::read_lines(&file).and_then(|content| match content {
+        Some(content) => ProjectShortcuts::deserialize(&file, &content.join("\n")).map(Some),
+        None => Ok(None),
+    });
+    match project {
+        Ok(project) => project,
+        Err(err) => {
+            let message = format!("Skipping project file: {}", err);
+            eprintln!("{}", message.yellow());
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_shortcuts() {
+        let file = Path::new("/home/user/repo/.shortcuts");
+        let content = r#"
+            [[shortcut]]
+            key = "api"
+            value = "services/api"
+
+            [[shortcut]]
+            key = "dl"
+            value = "/srv/downloads"
+        "#;
+        let project = ProjectShortcuts::deserialize(file, content).expect("Valid project file");
+        let api = Path::new("/home/user/repo").join("services/api");
+        assert_eq!("api", project.shortcuts[0].key);
+        assert_eq!(api.to_string_lossy(), project.shortcuts[0].value);
+        assert_eq!("/srv/downloads", project.shortcuts[1].value);
+
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![
+                ShortcutKV::new("dl".to_string(), "~/Downloads".to_string()),
+                ShortcutKV::new("repo".to_string(), "/home/user/repo".to_string()),
+            ],
+        };
+        let layered = config.with_project(&project);
+        let values: Vec<&str> = layered.shortcuts.iter().map(|x| &x.value[..]).collect();
+        assert_eq!(
+            vec![
+                &api.to_string_lossy()[..],
+                "/srv/downloads",
+                "/home/user/repo"
+            ],
+            values
+        );
+
+        assert!(ProjectShortcuts::deserialize(file, "[[shortcut]]\nkey = 1").is_err());
+    }
+
+    #[test]
+    fn test_broken_project_file() {
+        let dir = std::env::temp_dir().join(format!("shortcut-project-{}", std::process::id()));
+        let sub_dir = dir.join("src");
+        fs::ensure_dir(&sub_dir).expect("Temp dir should be created");
+        let file = dir.join(PROJECT_FILE_NAME);
+        fs::write_lines(&file, &["[[shortcut]]".to_string(), "key =".to_string()]).unwrap();
+        assert_eq!(None, find_project_shortcuts(&sub_dir));
+
+        let content = ["[[shortcut]]", "key = \"api\"", "value = \"api\""];
+        fs::write_lines(&file, &content.map(String::from)).unwrap();
+        let project = find_project_shortcuts(&sub_dir).expect("Valid project file");
+        assert_eq!(file, project.file);
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
+}
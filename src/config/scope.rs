@@ -0,0 +1,148 @@
+use {
+    super::{Config, ShortcutKV},
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+/// Machines a shortcut is used on, so a config shared between machines can have a different
+/// target for the same key on each of them. A scope without host nor operating system applies to
+/// every machine.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scope {
+    /// Host name of the only machine the shortcut is used on, compared ignoring case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Operating system the shortcut is used on, as in `std::env::consts::OS`, e.g. `linux`,
+    /// `macos` or `windows`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+}
+
+/// Host name and operating system of a machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub host: String,
+    pub os: String,
+}
+
+impl Machine {
+    /// The machine shortcut is running on.
+    pub fn current() -> Self {
+        Machine {
+            host: gethostname::gethostname().to_string_lossy().into_owned(),
+            os: std::env::consts::OS.to_string(),
+        }
+    }
+}
+
+impl Scope {
+    pub fn is_global(&self) -> bool {
+        self.host.is_none() && self.os.is_none()
+    }
+
+    pub fn applies_to(&self, machine: &Machine) -> bool {
+        let host_matches = self
+            .host
+            .as_ref()
+            .is_none_or(|host| host.eq_ignore_ascii_case(&machine.host));
+        let os_matches = self
+            .os
+            .as_ref()
+            .is_none_or(|os| os.eq_ignore_ascii_case(&machine.os));
+        host_matches && os_matches
+    }
+
+    /// How specific the scope is, a host is more specific than an operating system.
+    fn specificity(&self) -> u8 {
+        2 * self.host.is_some() as u8 + self.os.is_some() as u8
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.host, &self.os) {
+            (None, None) => write!(f, "every machine"),
+            (Some(host), None) => write!(f, "host {}", host),
+            (None, Some(os)) => write!(f, "os {}", os),
+            (Some(host), Some(os)) => write!(f, "host {}, os {}", host, os),
+        }
+    }
+}
+
+impl Config {
+    /// The shortcuts used on `machine`: for every key, the variant with the most specific scope
+    /// that applies to the machine.
+    pub fn active_shortcuts(&self, machine: &Machine) -> Vec<&ShortcutKV> {
+        let mut active: Vec<&ShortcutKV> = vec![];
+        for shortcut in &self.shortcuts {
+            if !shortcut.scope.applies_to(machine) {
+                continue;
+            }
+            match active.iter_mut().find(|x| x.key == shortcut.key) {
+                Some(existing) => {
+                    if shortcut.scope.specificity() > existing.scope.specificity() {
+                        *existing = shortcut;
+                    }
+                }
+                None => active.push(shortcut),
+            }
+        }
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(key: &str, value: &str, host: Option<&str>, os: Option<&str>) -> ShortcutKV {
+        ShortcutKV {
+            key: key.to_string(),
+            value: value.to_string(),
+            scope: Scope {
+                host: host.map(String::from),
+                os: os.map(String::from),
+            },
+            literal: false,
+        }
+    }
+
+    #[test]
+    fn test_active_shortcuts() {
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![
+                shortcut("repo", "~/code", None, None),
+                shortcut("repo", "C:\\code", None, Some("windows")),
+                shortcut("repo", "/srv/code", Some("server"), None),
+                shortcut("dl", "~/Downloads", None, None),
+                shortcut("tmp", "/tmp", None, Some("linux")),
+            ],
+        };
+        let active = |host: &str, os: &str| -> Vec<String> {
+            let machine = Machine {
+                host: host.to_string(),
+                os: os.to_string(),
+            };
+            config
+                .active_shortcuts(&machine)
+                .into_iter()
+                .map(|x| format!("{} {}", x.key, x.value))
+                .collect()
+        };
+        assert_eq!(
+            vec!["repo /srv/code", "dl ~/Downloads", "tmp /tmp"],
+            active("SERVER", "linux")
+        );
+        assert_eq!(
+            vec!["repo C:\\code", "dl ~/Downloads"],
+            active("laptop", "windows")
+        );
+        assert_eq!(
+            vec!["repo ~/code", "dl ~/Downloads"],
+            active("mac", "macos")
+        );
+    }
+}
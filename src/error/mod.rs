@@ -0,0 +1,183 @@
+use std::{fmt, io, path::PathBuf};
+
+/// Errors returned by the library.
+#[derive(Debug)]
+pub enum Error {
+    /// The config file does not exist, one-time setup needs to be run.
+    ConfigNotFound,
+    /// The config file exists but its content could not be deserialized.
+    CorruptedConfig { path: PathBuf, reason: String },
+    /// Config content could not be deserialized.
+    InvalidConfig(String),
+    /// A file being imported could not be parsed.
+    InvalidImport { path: PathBuf, reason: String },
+    /// No home directory could be retrieved from the operating system.
+    NoHomeDirectory,
+    /// The given path was expected to be an existing directory.
+    NotADirectory(PathBuf),
+    /// The given path could not be converted to an absolute path.
+    InvalidPath { path: PathBuf, source: io::Error },
+    /// Permission denied while `action` was being done on `path`.
+    PermissionDenied {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Any other I/O error while `action` was being done on `path`.
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Several shortcuts match the query equally well, candidates are (key, target) pairs.
+    AmbiguousShortcut {
+        query: String,
+        candidates: Vec<(String, String)>,
+    },
+    /// Error reading from or drawing on the terminal.
+    Terminal(io::Error),
+    /// Error finding or inspecting the installation of a shell.
+    Shell {
+        shell: &'static str,
+        message: String,
+    },
+    /// A shell profile can not be changed safely, e.g. its shortcut block was edited by hand.
+    EditedProfile { path: PathBuf, reason: String },
+    /// A shell name given in the command line is not in the registry, `known` are the valid ones.
+    UnknownShell {
+        name: String,
+        known: Vec<&'static str>,
+    },
+    /// `doctor` found the given number of problems and was not asked to fix them.
+    ProblemsFound(usize),
+}
+
+impl Error {
+    /// Creates an `Error::PermissionDenied` or `Error::Io` depending on the kind of `source`.
+    pub fn io(action: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        let path = path.into();
+        match source.kind() {
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied {
+                action,
+                path,
+                source,
+            },
+            _ => Error::Io {
+                action,
+                path,
+                source,
+            },
+        }
+    }
+
+    /// Process exit code used by the `shortcut` binary when the command fails with this error.
+    /// 1 is used for generic failures and 2 for usage errors, as clap does.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::ConfigNotFound => 3,
+            Error::CorruptedConfig { .. }
+            | Error::InvalidConfig(_)
+            | Error::InvalidImport { .. } => 4,
+            Error::PermissionDenied { .. } => 5,
+            Error::Io { .. } | Error::Terminal(_) => 6,
+            Error::NotADirectory(_) | Error::InvalidPath { .. } | Error::NoHomeDirectory => 7,
+            Error::AmbiguousShortcut { .. } => 8,
+            Error::Shell { .. } => 1,
+            Error::UnknownShell { .. } => 2,
+            Error::EditedProfile { .. } => 9,
+            Error::ProblemsFound(_) => 10,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ConfigNotFound => {
+                write!(f, "Config file not found. Run one-time setup (see --help)")
+            }
+            Error::CorruptedConfig { path, reason } => {
+                write!(
+                    f,
+                    "Corrupted config file: \"{}\".\n{}",
+                    path.display(),
+                    reason
+                )
+            }
+            Error::InvalidConfig(reason) => write!(f, "{}", reason),
+            Error::InvalidImport { path, reason } => {
+                write!(f, "Could not import \"{}\": {}", path.display(), reason)
+            }
+            Error::NoHomeDirectory => write!(
+                f,
+                "No valid home directory path could be retrieved from the operating system."
+            ),
+            Error::NotADirectory(path) => write!(f, "\"{}\" is not a directory", path.display()),
+            Error::InvalidPath { path, source } => {
+                write!(f, "Error parsing path \"{}\": {}", path.display(), source)
+            }
+            Error::PermissionDenied { action, path, .. } => {
+                write!(f, "Permission denied {} \"{}\"", action, path.display())
+            }
+            Error::Io {
+                action,
+                path,
+                source,
+            } => write!(f, "Error {} \"{}\": {}", action, path.display(), source),
+            Error::AmbiguousShortcut { query, candidates } => {
+                write!(f, "\"{}\" matches several shortcuts:", query)?;
+                for (key, target) in candidates {
+                    write!(f, "\n  {} -> {}", key, target)?;
+                }
+                Ok(())
+            }
+            Error::Terminal(source) => write!(f, "Error using the terminal: {}", source),
+            Error::Shell { shell, message } => write!(f, "{}: {}", shell, message),
+            Error::EditedProfile { path, reason } => {
+                write!(f, "Refusing to change \"{}\": {}", path.display(), reason)
+            }
+            Error::UnknownShell { name, known } => {
+                write!(
+                    f,
+                    "Unknown shell \"{}\", expected one of: {}",
+                    name,
+                    known.join(", ")
+                )
+            }
+            Error::ProblemsFound(problems) => {
+                write!(f, "Found {} problems, run with --fix to fix them", problems)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidPath { source, .. }
+            | Error::PermissionDenied { source, .. }
+            | Error::Io { source, .. }
+            | Error::Terminal(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::error::Error as _};
+
+    #[test]
+    fn test_io_error_kind() {
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        let err = Error::io("reading file", "/etc/shadow", denied);
+        assert!(matches!(err, Error::PermissionDenied { .. }));
+        assert_eq!(5, err.exit_code());
+        assert!(err.source().is_some());
+
+        let other = io::Error::from(io::ErrorKind::InvalidData);
+        let err = Error::io("reading file", "/tmp/file", other);
+        assert!(matches!(err, Error::Io { .. }));
+        assert_eq!(6, err.exit_code());
+    }
+}
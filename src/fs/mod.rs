@@ -1,25 +1,25 @@
-use std::{
-    io::ErrorKind,
-    path::{Path, PathBuf},
+use {
+    crate::error::Error,
+    std::{
+        io::ErrorKind,
+        path::{Path, PathBuf},
+    },
 };
 
+mod text;
+
+pub use text::{Encoding, TextFormat, backup_file, find_latest_backup, read_text, write_text};
+
 /// Ensures the given directory exists, creating as many of the parents directories as needed.
-pub fn ensure_dir(directory: &Path) -> Result<(), String> {
+pub fn ensure_dir(directory: &Path) -> Result<(), Error> {
     match std::fs::create_dir_all(directory) {
         Ok(_) => Ok(()),
-        Err(err) => {
-            let msg = format!(
-                "Error creating directory \"{}\": {}",
-                directory.display(),
-                err
-            );
-            Err(msg.to_string())
-        }
+        Err(err) => Err(Error::io("creating directory", directory, err)),
     }
 }
 
 /// Ensures the given directory exists, creating as many of the parents directories as needed.
-pub fn ensure_file_parent_dir(file: &Path) -> Result<(), String> {
+pub fn ensure_file_parent_dir(file: &Path) -> Result<(), Error> {
     match file.parent() {
         Some(dir) => ensure_dir(dir),
         None => Ok(()),
@@ -28,71 +28,118 @@ pub fn ensure_file_parent_dir(file: &Path) -> Result<(), String> {
 
 /// Reads the given file content into a vector of strings.
 /// Returns Ok(None) when the file does not exists.
-pub fn read_lines(file: &Path) -> Result<Option<Vec<String>>, String> {
+pub fn read_lines(file: &Path) -> Result<Option<Vec<String>>, Error> {
     match std::fs::read_to_string(file) {
         Ok(content) => Ok(Some(content.lines().map(String::from).collect())),
         Err(error) => match error.kind() {
             ErrorKind::NotFound => Ok(None),
-            ErrorKind::PermissionDenied => {
-                let msg = format!("Permission denied to open file: {}", file.display());
-                Err(msg.to_string())
-            }
-            _ => Err(format!("Error reading file: {}", file.display()).to_string()),
+            _ => Err(Error::io("reading file", file, error)),
+        },
+    }
+}
+
+/// Reads the given file content as bytes.
+/// Returns Ok(None) when the file does not exists.
+pub fn read_bytes(file: &Path) -> Result<Option<Vec<u8>>, Error> {
+    match std::fs::read(file) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) => match error.kind() {
+            ErrorKind::NotFound => Ok(None),
+            _ => Err(Error::io("reading file", file, error)),
         },
     }
 }
 
 /// Writes the given content to the file, overwritting existing file content.
-pub fn write_str(file: &Path, content: &str) -> Result<(), String> {
+pub fn write_str(file: &Path, content: &str) -> Result<(), Error> {
+    write_bytes(file, content.as_bytes())
+}
+
+/// Writes the given bytes to the file, overwritting existing file content.
+pub fn write_bytes(file: &Path, content: &[u8]) -> Result<(), Error> {
     match std::fs::write(file, content) {
         Ok(()) => {
             println!("Updated file \"{}\"", file.display());
             Ok(())
         }
+        Err(error) => Err(Error::io("writing to file", file, error)),
+    }
+}
+
+/// Moves the file `from` to `to`, replacing `to` if it exists.
+pub fn rename(from: &Path, to: &Path) -> Result<(), Error> {
+    match std::fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(Error::io("moving file", from, error)),
+    }
+}
+
+/// Deletes the given file.
+/// Returns Ok(false) when the file does not exists.
+pub fn remove_file(file: &Path) -> Result<bool, Error> {
+    match std::fs::remove_file(file) {
+        Ok(()) => Ok(true),
         Err(error) => match error.kind() {
-            ErrorKind::NotFound => {
-                let msg = format!(
-                    "Could not write to file {} because it was not found",
-                    file.display()
-                );
-                return Err(msg.to_string());
-            }
-            ErrorKind::PermissionDenied => {
-                let msg = format!("Permission denied to write to file {}", file.display());
-                return Err(msg.to_string());
-            }
-            _ => {
-                let msg = format!("Error writing to file {}", file.display());
-                return Err(msg.to_string());
-            }
+            ErrorKind::NotFound => Ok(false),
+            _ => Err(Error::io("deleting file", file, error)),
         },
     }
 }
 
 /// Writes the given vector of lines into the file.
-pub fn write_lines(file: &Path, lines: &Vec<String>) -> Result<(), String> {
+pub fn write_lines(file: &Path, lines: &[String]) -> Result<(), Error> {
     let fs_file = match std::fs::File::create(file) {
         Ok(value) => value,
-        Err(err) => {
-            return Err(format!("Error reading file: '{}': {}", file.display(), err).to_string());
-        }
+        Err(err) => return Err(Error::io("creating file", file, err)),
     };
     let mut writer = std::io::BufWriter::new(fs_file);
     use std::io::Write;
     for line in lines {
         if let Err(err) = writeln!(writer, "{}", line) {
-            return Err(format!("Error writing file '{}': {}", file.display(), err).to_string());
+            return Err(Error::io("writing to file", file, err));
         }
     }
     if let Err(err) = writer.flush() {
-        return Err(format!("Error saving file '{}': {}", file.display(), err).to_string());
+        return Err(Error::io("saving file", file, err));
     }
     Ok(())
 }
 
+/// Writes the given vector of lines into a temporary file next to `file` and then renames it
+/// over `file`, so readers see either the old or the new content but never a partial write.
+pub fn write_lines_atomic(file: &Path, lines: &[String]) -> Result<(), Error> {
+    let mut temp_file = file.as_os_str().to_owned();
+    temp_file.push(format!(".{}.tmp", std::process::id()));
+    let temp_file = PathBuf::from(temp_file);
+    write_lines(&temp_file, lines)?;
+    if let Err(err) = std::fs::rename(&temp_file, file) {
+        let _ = std::fs::remove_file(&temp_file);
+        return Err(Error::io("saving file", file, err));
+    }
+    Ok(())
+}
+
+/// Opens (creating if needed) the given file and blocks until an exclusive advisory lock on it
+/// is acquired. The lock is released when the returned file is dropped.
+pub fn lock_file(file: &Path) -> Result<std::fs::File, Error> {
+    let fs_file = match std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(file)
+    {
+        Ok(value) => value,
+        Err(err) => return Err(Error::io("opening lock file", file, err)),
+    };
+    if let Err(err) = fs_file.lock() {
+        return Err(Error::io("locking file", file, err));
+    }
+    Ok(fs_file)
+}
+
 /// Converts a path to an absolute path, replacing the staring '~' component with the home directory
 /// if needed.
-pub fn to_absolute_path(path: &Path) -> Result<PathBuf, String> {
+pub fn to_absolute_path(path: &Path) -> Result<PathBuf, Error> {
     // handle paths starting with ~, replace the ~ component with the home directory
     if let Some(first_component) = path.components().next()
         && first_component.as_os_str() == "~"
@@ -117,12 +164,129 @@ pub fn to_absolute_path(path: &Path) -> Result<PathBuf, String> {
     to_absolute_path_internal(path)
 }
 
-fn to_absolute_path_internal(path: &Path) -> Result<PathBuf, String> {
-    match std::path::absolute(&path) {
-        Ok(path) => Ok(path),
-        Err(err) => {
-            let msg = format!("Error parsing path \"{}\": {}", path.display(), err);
-            return Err(msg);
+/// Expands a path stored literally in the config: a leading `~` and environment variables written
+/// as `$VAR`, `${VAR}`, `${env:VAR}` or `%VAR%`, so the same path works on every platform.
+/// `HOME` and `USERPROFILE` fall back to the home directory when they are not set, undefined
+/// variables are left as they are. If anything was expanded, `\\` and `/` are both treated as
+/// path separators.
+pub fn expand_path(value: &str) -> Result<PathBuf, Error> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut changed = false;
+    if let Some(after_tilde) = value.strip_prefix('~')
+        && (after_tilde.is_empty() || after_tilde.starts_with(['/', '\\']))
+    {
+        expanded.push_str(&get_home_dir()?.to_string_lossy());
+        rest = after_tilde;
+        changed = true;
+    }
+    while let Some(start) = rest.find(['$', '%']) {
+        expanded.push_str(&rest[..start]);
+        let (name, len) = match parse_variable(&rest[start..]) {
+            Some(variable) => variable,
+            None => {
+                expanded.push_str(&rest[start..start + 1]);
+                rest = &rest[start + 1..];
+                continue;
+            }
+        };
+        match get_variable(name)? {
+            Some(variable_value) => {
+                expanded.push_str(&variable_value);
+                changed = true;
+            }
+            None => expanded.push_str(&rest[start..start + len]),
+        }
+        rest = &rest[start + len..];
+    }
+    expanded.push_str(rest);
+    if changed && std::path::MAIN_SEPARATOR == '/' {
+        expanded = expanded.replace('\\', "/");
+    }
+    Ok(PathBuf::from(expanded))
+}
+
+/// Parses the variable reference at the start of `text`, returning the variable name and the
+/// length of the reference.
+fn parse_variable(text: &str) -> Option<(&str, usize)> {
+    let is_name = |name: &str| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if let Some(braced) = text.strip_prefix("${") {
+        let end = braced.find('}')?;
+        let name = &braced[..end];
+        let name = name.strip_prefix("env:").unwrap_or(name);
+        return is_name(name).then_some((name, end + 3));
+    }
+    if let Some(unbraced) = text.strip_prefix('$') {
+        let end = unbraced
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(unbraced.len());
+        let name = &unbraced[..end];
+        return is_name(name).then_some((name, end + 1));
+    }
+    let percent = text.strip_prefix('%')?;
+    let end = percent.find('%')?;
+    let name = &percent[..end];
+    is_name(name).then_some((name, end + 2))
+}
+
+fn get_variable(name: &str) -> Result<Option<String>, Error> {
+    match std::env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) if name == "HOME" || name == "USERPROFILE" => {
+            Ok(Some(get_home_dir()?.to_string_lossy().into_owned()))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+fn get_home_dir() -> Result<PathBuf, Error> {
+    match directories::UserDirs::new() {
+        Some(user_dirs) => Ok(user_dirs.home_dir().to_path_buf()),
+        None => Err(Error::NoHomeDirectory),
+    }
+}
+
+fn to_absolute_path_internal(path: &Path) -> Result<PathBuf, Error> {
+    match std::path::absolute(path) {
+        Ok(absolute_path) => Ok(absolute_path),
+        Err(source) => Err(Error::InvalidPath {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_path() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let home = get_home_dir().unwrap().to_string_lossy().into_owned();
+        let expand = |value: &str| expand_path(value).unwrap().to_string_lossy().into_owned();
+        for value in [
+            "$CARGO_MANIFEST_DIR/src",
+            "${CARGO_MANIFEST_DIR}/src",
+            "${env:CARGO_MANIFEST_DIR}/src",
+            "%CARGO_MANIFEST_DIR%/src",
+        ] {
+            assert_eq!(format!("{}/src", manifest_dir), expand(value));
         }
+        assert_eq!(format!("{}/Downloads", home), expand("~/Downloads"));
+        assert_eq!(home, expand("~"));
+        assert_eq!("/srv/~user/$/100%", expand("/srv/~user/$/100%"));
+        assert_eq!(
+            "/srv/$UNDEFINED_SHORTCUT_VAR/x",
+            expand("/srv/$UNDEFINED_SHORTCUT_VAR/x")
+        );
+        assert_eq!("/srv/${x", expand("/srv/${x"));
+        #[cfg(not(windows))]
+        assert_eq!(
+            format!("{}/Code/repo", home),
+            expand("%USERPROFILE%\\Code\\repo")
+        );
     }
 }
@@ -0,0 +1,307 @@
+use {
+    super::write_bytes,
+    crate::error::Error,
+    std::{
+        fs::{File, OpenOptions},
+        io::ErrorKind,
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Infix of the backups of a file, e.g. `.bashrc.shortcut-backup-20250101-120000-000000`.
+const BACKUP_INFIX: &str = ".shortcut-backup-";
+/// Number of backups kept of each file, older ones are deleted.
+const MAX_BACKUPS: usize = 10;
+
+/// Encoding of a text file, detected from its byte order mark.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Neither UTF-8 nor UTF-16, e.g. Windows-1252. Every byte is kept as the char with the same
+    /// value so the file is written back byte by byte.
+    Latin1,
+}
+
+/// How the lines of a text file are stored, so the file can be written back the way it was.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextFormat {
+    pub encoding: Encoding,
+    /// Lines end with `\r\n` instead of `\n`.
+    pub crlf: bool,
+    /// The last line ends with a line ending.
+    pub final_newline: bool,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        TextFormat {
+            encoding: Encoding::Utf8,
+            crlf: false,
+            final_newline: true,
+        }
+    }
+}
+
+impl TextFormat {
+    /// Splits the content of a text file into lines, detecting its format.
+    pub fn decode(bytes: &[u8]) -> (Vec<String>, TextFormat) {
+        let (encoding, text) = if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+            (
+                Encoding::Utf8Bom,
+                String::from_utf8_lossy(rest).into_owned(),
+            )
+        } else if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+            let units = rest
+                .chunks_exact(2)
+                .map(|x| u16::from_le_bytes([x[0], x[1]]));
+            (Encoding::Utf16Le, decode_utf16(units))
+        } else if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+            let units = rest
+                .chunks_exact(2)
+                .map(|x| u16::from_be_bytes([x[0], x[1]]));
+            (Encoding::Utf16Be, decode_utf16(units))
+        } else {
+            match std::str::from_utf8(bytes) {
+                Ok(text) => (Encoding::Utf8, text.to_string()),
+                Err(_) => (Encoding::Latin1, bytes.iter().map(|&x| x as char).collect()),
+            }
+        };
+        let format = TextFormat {
+            encoding,
+            crlf: text.find('\n').is_some_and(|i| text[..i].ends_with('\r')),
+            final_newline: text.is_empty() || text.ends_with('\n'),
+        };
+        let lines = text.lines().map(String::from).collect();
+        (lines, format)
+    }
+
+    /// Joins the lines into the content of a text file with this format.
+    pub fn encode(&self, lines: &[String]) -> Vec<u8> {
+        let line_ending = if self.crlf { "\r\n" } else { "\n" };
+        let mut text = lines.join(line_ending);
+        if self.final_newline && !lines.is_empty() {
+            text.push_str(line_ending);
+        }
+        match self.encoding {
+            Encoding::Utf8 => text.into_bytes(),
+            Encoding::Utf8Bom => [UTF8_BOM, text.as_bytes()].concat(),
+            Encoding::Utf16Le => {
+                let units = text.encode_utf16().flat_map(u16::to_le_bytes);
+                UTF16_LE_BOM.iter().copied().chain(units).collect()
+            }
+            Encoding::Utf16Be => {
+                let units = text.encode_utf16().flat_map(u16::to_be_bytes);
+                UTF16_BE_BOM.iter().copied().chain(units).collect()
+            }
+            Encoding::Latin1 => text
+                .chars()
+                .map(|x| u8::try_from(x).unwrap_or(b'?'))
+                .collect(),
+        }
+    }
+}
+
+fn decode_utf16(units: impl Iterator<Item = u16>) -> String {
+    char::decode_utf16(units)
+        .map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Reads the lines of a text file and its format.
+/// Returns Ok(None) when the file does not exists.
+pub fn read_text(file: &Path) -> Result<Option<(Vec<String>, TextFormat)>, Error> {
+    match std::fs::read(file) {
+        Ok(content) => Ok(Some(TextFormat::decode(&content))),
+        Err(error) => match error.kind() {
+            ErrorKind::NotFound => Ok(None),
+            _ => Err(Error::io("reading file", file, error)),
+        },
+    }
+}
+
+/// Writes the lines into the file with the given format.
+pub fn write_text(file: &Path, lines: &[String], format: &TextFormat) -> Result<(), Error> {
+    write_bytes(file, &format.encode(lines))
+}
+
+/// Copies the file to a backup next to it named after the current time, returns the backup.
+/// An existing backup is never overwritten, and only the latest `MAX_BACKUPS` backups are kept.
+pub fn backup_file(file: &Path) -> Result<PathBuf, Error> {
+    let mut micros = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_micros() as u64)
+        .unwrap_or(0);
+    let mut source = match File::open(file) {
+        Ok(source) => source,
+        Err(err) => return Err(Error::io("backing up file", file, err)),
+    };
+    // Two backups in the same microsecond get consecutive timestamps instead.
+    let (backup, mut destination) = loop {
+        let backup = get_backup_path(file, micros);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&backup)
+        {
+            Ok(destination) => break (backup, destination),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => micros += 1,
+            Err(err) => return Err(Error::io("backing up file", file, err)),
+        }
+    };
+    // Permissions are copied too, as the backup may replace the file in `restore-profile`.
+    let copied = std::io::copy(&mut source, &mut destination)
+        .and_then(|_| source.metadata())
+        .and_then(|metadata| destination.set_permissions(metadata.permissions()));
+    if let Err(err) = copied {
+        return Err(Error::io("backing up file", file, err));
+    }
+    let backups = find_backups(file)?;
+    for old_backup in &backups[..backups.len().saturating_sub(MAX_BACKUPS)] {
+        if let Err(err) = std::fs::remove_file(old_backup) {
+            return Err(Error::io("removing old backup", old_backup, err));
+        }
+    }
+    Ok(backup)
+}
+
+/// The most recent backup of the file made by `backup_file`, if any.
+pub fn find_latest_backup(file: &Path) -> Result<Option<PathBuf>, Error> {
+    Ok(find_backups(file)?.pop())
+}
+
+fn get_backup_path(file: &Path, micros: u64) -> PathBuf {
+    let mut backup = file.as_os_str().to_owned();
+    backup.push(BACKUP_INFIX);
+    backup.push(format!(
+        "{}-{:06}",
+        format_timestamp(micros / 1_000_000),
+        micros % 1_000_000
+    ));
+    PathBuf::from(backup)
+}
+
+/// Every backup of the file made by `backup_file`, from the oldest to the latest.
+fn find_backups(file: &Path) -> Result<Vec<PathBuf>, Error> {
+    let (dir, name) = match (file.parent(), file.file_name()) {
+        (Some(dir), Some(name)) => (dir, name.to_string_lossy() + BACKUP_INFIX),
+        _ => return Ok(vec![]),
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(Error::io("reading directory", dir, err)),
+    };
+    // Timestamps have a fixed width so later backups have greater names.
+    let mut backups: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|x| x.starts_with(&*name))
+        .collect();
+    backups.sort();
+    Ok(backups.into_iter().map(|x| dir.join(x)).collect())
+}
+
+/// Formats seconds since the Unix epoch as a UTC `YYYYMMDD-HHMMSS` timestamp.
+fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+    // Civil date from days since the epoch, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_format() {
+        let round_trip = |bytes: &[u8], expected_format: TextFormat| {
+            let (lines, format) = TextFormat::decode(bytes);
+            assert_eq!(vec!["a", "é"], lines);
+            assert_eq!(expected_format, format);
+            assert_eq!(bytes, &format.encode(&lines)[..]);
+        };
+        round_trip("a\né\n".as_bytes(), TextFormat::default());
+        let crlf = TextFormat {
+            crlf: true,
+            final_newline: false,
+            ..TextFormat::default()
+        };
+        round_trip("a\r\né".as_bytes(), crlf);
+        let bom = TextFormat {
+            encoding: Encoding::Utf8Bom,
+            ..TextFormat::default()
+        };
+        round_trip(&[UTF8_BOM, "a\né\n".as_bytes()].concat(), bom);
+        let utf16 = TextFormat {
+            encoding: Encoding::Utf16Le,
+            crlf: true,
+            final_newline: true,
+        };
+        round_trip(
+            &[
+                0xFF, 0xFE, b'a', 0, b'\r', 0, b'\n', 0, 0xE9, 0, b'\r', 0, b'\n', 0,
+            ],
+            utf16,
+        );
+        let latin1 = TextFormat {
+            encoding: Encoding::Latin1,
+            ..TextFormat::default()
+        };
+        round_trip(&[b'a', b'\n', 0xE9, b'\n'], latin1);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!("19700101-000000", format_timestamp(0));
+        assert_eq!("20240229-235959", format_timestamp(1709251199));
+    }
+
+    #[test]
+    fn test_backups() {
+        let dir = std::env::temp_dir().join(format!("shortcut-backups-{}", std::process::id()));
+        crate::fs::ensure_dir(&dir).expect("Temp dir should be created");
+        let file = dir.join(".bashrc");
+        assert_eq!(None, find_latest_backup(&file).unwrap());
+
+        let mut backups = vec![];
+        for i in 0..MAX_BACKUPS + 2 {
+            std::fs::write(&file, i.to_string()).unwrap();
+            backups.push(backup_file(&file).unwrap());
+        }
+        let kept = find_backups(&file).unwrap();
+        assert_eq!(backups[2..], kept[..]);
+        let latest = find_latest_backup(&file).unwrap().unwrap();
+        assert_eq!(
+            (MAX_BACKUPS + 1).to_string(),
+            std::fs::read_to_string(latest).unwrap()
+        );
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
+}
@@ -0,0 +1,98 @@
+//! Bash aliases, one `alias {key}='cd -- {target}'` line per shortcut. Literal targets are
+//! exported expanded, as the quoted alias would not expand them.
+
+use crate::config::ShortcutKV;
+
+pub fn export(shortcuts: &[ShortcutKV]) -> String {
+    let mut content = String::new();
+    for shortcut in shortcuts {
+        if !is_valid_alias_name(&shortcut.key) {
+            eprintln!(
+                "Skipping shortcut \"{}\", it is not a valid alias name",
+                shortcut.key
+            );
+            continue;
+        }
+        let target = match shortcut.target() {
+            Ok(target) => target.to_string_lossy().into_owned(),
+            Err(err) => {
+                eprintln!("Skipping shortcut \"{}\", {}", shortcut.key, err);
+                continue;
+            }
+        };
+        if target.contains(['\r', '\n']) {
+            eprintln!(
+                "Skipping shortcut \"{}\", aliases are one per line",
+                shortcut.key
+            );
+            continue;
+        }
+        let command = format!("cd -- {}", quote(&target));
+        content.push_str(&format!("alias {}={}\n", shortcut.key, quote(&command)));
+    }
+    content
+}
+
+pub fn import(content: &str) -> Result<Vec<ShortcutKV>, String> {
+    let mut shortcuts = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        let definition = match line.strip_prefix("alias ") {
+            Some(definition) => definition.trim_start(),
+            None => continue,
+        };
+        let invalid_line = || format!("Invalid alias in line {}: {}", i + 1, line);
+        let (key, command) = definition.split_once('=').ok_or_else(invalid_line)?;
+        let command = unquote(command).ok_or_else(invalid_line)?;
+        let target = match command.trim_start().strip_prefix("cd ") {
+            Some(target) => target.trim_start(),
+            // Not a directory alias, e.g. `alias ll='ls -l'`.
+            None => continue,
+        };
+        let target = target.strip_prefix("-- ").unwrap_or(target);
+        let value = unquote(target).ok_or_else(invalid_line)?;
+        shortcuts.push(ShortcutKV::new(key.to_string(), value));
+    }
+    Ok(shortcuts)
+}
+
+fn is_valid_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '+' | '@' | ':'))
+}
+
+/// Single quotes the word so the shell does not expand anything in it.
+fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Removes the shell quoting of a single word, supporting single quotes, double quotes and
+/// backslash escapes. Returns `None` if the quotes are unbalanced or there is more than one word.
+fn unquote(word: &str) -> Option<String> {
+    let mut unquoted = String::new();
+    let mut chars = word.trim().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next()? {
+                    '\'' => break,
+                    c => unquoted.push(c),
+                }
+            },
+            '"' => loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => unquoted.push(chars.next()?),
+                    c => unquoted.push(c),
+                }
+            },
+            '\\' => unquoted.push(chars.next()?),
+            c if c.is_whitespace() => return None,
+            c => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
@@ -0,0 +1,56 @@
+//! autojump (https://github.com/wting/autojump) stores one `weight\tpath` line per directory.
+
+use {
+    super::ImportedDir,
+    crate::{error::Error, fs},
+    directories::BaseDirs,
+    std::path::PathBuf,
+};
+
+pub fn default_database() -> Result<PathBuf, Error> {
+    let base_dirs = BaseDirs::new().ok_or(Error::NoHomeDirectory)?;
+    let data_dir = if cfg!(target_os = "macos") {
+        base_dirs.home_dir().join("Library")
+    } else {
+        // `data_dir` is `$XDG_DATA_HOME` on Linux and `%APPDATA%` on Windows, as for autojump.
+        base_dirs.data_dir().to_path_buf()
+    };
+    fs::to_absolute_path(&data_dir.join("autojump").join("autojump.txt"))
+}
+
+pub fn parse(lines: &[String]) -> Result<Vec<ImportedDir>, String> {
+    let mut dirs = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.splitn(2, '\t');
+        let weight = fields.next().and_then(|x| x.trim().parse::<f64>().ok());
+        match (weight, fields.next()) {
+            (Some(weight), Some(path)) => dirs.push(ImportedDir {
+                path: path.to_string(),
+                score: weight,
+            }),
+            _ => return Err(format!("Invalid line {}: \"{}\"", i + 1, line)),
+        }
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let lines: Vec<String> = vec!["10.0\t/home/user/code", "", "22.36\t/home/user/with\ttab"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let dirs = parse(&lines).expect("Should parse");
+        assert_eq!(2, dirs.len());
+        assert_eq!("/home/user/code", dirs[0].path);
+        assert_eq!(22.36, dirs[1].score);
+        assert_eq!("/home/user/with\ttab", dirs[1].path);
+    }
+}
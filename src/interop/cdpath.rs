@@ -0,0 +1,39 @@
+//! `CDPATH` variable listing the shortcut targets, so `cd` finds the directories inside them.
+//! Keys are not part of the format, imported shortcuts are named after the directories.
+
+use {super::ImportedDir, crate::config::ShortcutKV};
+
+#[cfg(windows)]
+const SEPARATOR: char = ';';
+#[cfg(not(windows))]
+const SEPARATOR: char = ':';
+
+pub fn export(shortcuts: &[ShortcutKV]) -> String {
+    let mut dirs: Vec<String> = vec![".".to_string()];
+    dirs.extend(
+        shortcuts
+            .iter()
+            .filter_map(|x| x.target().ok())
+            .map(|x| x.to_string_lossy().into_owned()),
+    );
+    format!("export CDPATH=\"{}\"\n", dirs.join(&SEPARATOR.to_string()))
+}
+
+pub fn import(content: &str) -> Result<Vec<ImportedDir>, String> {
+    let line = content
+        .lines()
+        .map(|x| x.trim())
+        .find(|x| !x.is_empty() && !x.starts_with('#'))
+        .ok_or("No CDPATH found")?;
+    let value = line.strip_prefix("export ").unwrap_or(line);
+    let value = value.strip_prefix("CDPATH=").unwrap_or(value);
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    Ok(value
+        .split(SEPARATOR)
+        .filter(|x| !x.is_empty() && *x != ".")
+        .map(|x| ImportedDir {
+            path: x.to_string(),
+            score: 0.0,
+        })
+        .collect())
+}
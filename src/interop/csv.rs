@@ -0,0 +1,103 @@
+//! CSV with a `key,value,host,os` header, quoted as described in RFC 4180. Host and OS are empty
+//! for shortcuts used on every machine, files with only the `key,value` columns are imported too.
+
+use crate::config::{Scope, ShortcutKV};
+
+const HEADER: [&str; 4] = ["key", "value", "host", "os"];
+
+pub fn export(shortcuts: &[ShortcutKV]) -> String {
+    let mut content = format!("{}\r\n", HEADER.join(","));
+    for shortcut in shortcuts {
+        content.push_str(&format!(
+            "{},{},{},{}\r\n",
+            quote(&shortcut.key),
+            quote(&shortcut.value),
+            quote(shortcut.scope.host.as_deref().unwrap_or("")),
+            quote(shortcut.scope.os.as_deref().unwrap_or(""))
+        ));
+    }
+    content
+}
+
+pub fn import(content: &str) -> Result<Vec<ShortcutKV>, String> {
+    let mut records = parse(content)?;
+    if records
+        .first()
+        .is_some_and(|x| x == &HEADER || x == &HEADER[..2])
+    {
+        records.remove(0);
+    }
+    let non_empty = |field: String| Some(field).filter(|x| !x.is_empty());
+    let mut shortcuts = Vec::with_capacity(records.len());
+    for (i, record) in records.into_iter().enumerate() {
+        let record = match <[String; 4]>::try_from(record) {
+            Ok([key, value, host, os]) => {
+                shortcuts.push(ShortcutKV {
+                    scope: Scope {
+                        host: non_empty(host),
+                        os: non_empty(os),
+                    },
+                    ..ShortcutKV::new(key, value)
+                });
+                continue;
+            }
+            Err(record) => record,
+        };
+        match <[String; 2]>::try_from(record) {
+            Ok([key, value]) => shortcuts.push(ShortcutKV::new(key, value)),
+            Err(record) => {
+                return Err(format!(
+                    "Record {} has {} fields, expected 2 or 4",
+                    i + 1,
+                    record.len()
+                ));
+            }
+        }
+    }
+    Ok(shortcuts)
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits the content into records of fields, empty lines are ignored.
+fn parse(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => in_quotes = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => (),
+            (false, '\n') => {
+                if !field.is_empty() || !record.is_empty() {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
@@ -0,0 +1,17 @@
+//! JSON array of `{"key": ..., "value": ...}` objects.
+
+use crate::config::ShortcutKV;
+
+pub fn export(shortcuts: &[ShortcutKV]) -> String {
+    let mut content = serde_json::to_string_pretty(shortcuts)
+        .expect("Shortcuts only contain strings, they must serialize");
+    content.push('\n');
+    content
+}
+
+pub fn import(content: &str) -> Result<Vec<ShortcutKV>, String> {
+    match serde_json::from_str(content) {
+        Ok(shortcuts) => Ok(shortcuts),
+        Err(err) => Err(format!("Invalid JSON: {}", err)),
+    }
+}
@@ -0,0 +1,397 @@
+use {
+    crate::{
+        config::{Config, Machine, ShortcutKV},
+        error::Error,
+        fs,
+    },
+    colored::Colorize,
+    std::{
+        collections::HashSet,
+        fmt,
+        path::{Path, PathBuf},
+    },
+};
+
+mod aliases;
+mod autojump;
+mod cdpath;
+mod csv;
+mod json;
+mod z;
+mod zoxide;
+
+/// Another directory jumping tool whose database can be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportSource {
+    Zoxide,
+    Autojump,
+    Z,
+    Fasd,
+}
+
+/// Format used to export and import shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Json,
+    Csv,
+    BashAliases,
+    Cdpath,
+}
+
+/// A directory read from the database of another tool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedDir {
+    /// Absolute path of the directory.
+    pub path: String,
+    /// Score given by the other tool, only comparable with scores from the same database.
+    pub score: f64,
+}
+
+impl fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportSource::Zoxide => write!(f, "zoxide"),
+            ImportSource::Autojump => write!(f, "autojump"),
+            ImportSource::Z => write!(f, "z"),
+            ImportSource::Fasd => write!(f, "fasd"),
+        }
+    }
+}
+
+impl ImportSource {
+    /// Location of the database used by the tool when it is not configured otherwise.
+    pub fn default_database(&self) -> Result<PathBuf, Error> {
+        match self {
+            ImportSource::Zoxide => zoxide::default_database(),
+            ImportSource::Autojump => autojump::default_database(),
+            ImportSource::Z => z::default_database("_Z_DATA", "~/.z"),
+            ImportSource::Fasd => z::default_database("_FASD_DATA", "~/.fasd"),
+        }
+    }
+
+    /// Reads all the directories in the given database, sorted by descending score.
+    pub fn read_database(&self, database: &Path) -> Result<Vec<ImportedDir>, Error> {
+        let not_found = || {
+            let source = std::io::Error::from(std::io::ErrorKind::NotFound);
+            Error::io("reading database", database, source)
+        };
+        let parsed = match self {
+            ImportSource::Zoxide => {
+                let content = fs::read_bytes(database)?.ok_or_else(not_found)?;
+                zoxide::parse(&content)
+            }
+            ImportSource::Autojump => {
+                let content = fs::read_lines(database)?.ok_or_else(not_found)?;
+                autojump::parse(&content)
+            }
+            ImportSource::Z | ImportSource::Fasd => {
+                let content = fs::read_lines(database)?.ok_or_else(not_found)?;
+                z::parse(&content)
+            }
+        };
+        let mut dirs = match parsed {
+            Ok(dirs) => dirs,
+            Err(reason) => {
+                return Err(Error::InvalidImport {
+                    path: database.to_path_buf(),
+                    reason,
+                });
+            }
+        };
+        dirs.sort_by(|x, y| y.score.total_cmp(&x.score));
+        Ok(dirs)
+    }
+}
+
+impl FileFormat {
+    /// Guesses the format from the file extension.
+    pub fn from_path(file: &Path) -> Option<FileFormat> {
+        let extension = file.extension()?.to_string_lossy().to_lowercase();
+        match &extension[..] {
+            "json" => Some(FileFormat::Json),
+            "csv" => Some(FileFormat::Csv),
+            "sh" | "bash" => Some(FileFormat::BashAliases),
+            _ => None,
+        }
+    }
+
+    pub fn export(&self, shortcuts: &[ShortcutKV]) -> String {
+        match self {
+            FileFormat::Json => json::export(shortcuts),
+            FileFormat::Csv => csv::export(shortcuts),
+            FileFormat::BashAliases => aliases::export(shortcuts),
+            FileFormat::Cdpath => cdpath::export(shortcuts),
+        }
+    }
+
+    /// Reads the shortcuts in `file`. Formats without keys get keys proposed as in
+    /// `propose_shortcuts`, skipping the directories that already have a shortcut in `config`.
+    /// The targets are stored as `resolve_targets` does.
+    pub fn import(&self, file: &Path, config: &Config) -> Result<Vec<ShortcutKV>, Error> {
+        let content = match fs::read_bytes(file)? {
+            Some(content) => content,
+            None => {
+                let source = std::io::Error::from(std::io::ErrorKind::NotFound);
+                return Err(Error::io("reading file", file, source));
+            }
+        };
+        let parsed = match String::from_utf8(content) {
+            Ok(content) => match self {
+                FileFormat::Json => json::import(&content),
+                FileFormat::Csv => csv::import(&content),
+                FileFormat::BashAliases => aliases::import(&content),
+                FileFormat::Cdpath => {
+                    cdpath::import(&content).map(|dirs| propose_shortcuts(config, &dirs))
+                }
+            },
+            Err(err) => Err(format!("Invalid UTF-8: {}", err)),
+        };
+        match parsed {
+            Ok(shortcuts) => resolve_targets(shortcuts, &Machine::current()),
+            Err(reason) => Err(Error::InvalidImport {
+                path: file.to_path_buf(),
+                reason,
+            }),
+        }
+    }
+}
+
+/// Stores imported targets the way `add` does. Relative values with `~`, `$` or `%` are kept
+/// `literal`, to be expanded when used, and the other values used on `machine` are made absolute.
+/// Shortcuts used on `machine` whose target is not a directory are skipped with a warning.
+pub fn resolve_targets(
+    shortcuts: Vec<ShortcutKV>,
+    machine: &Machine,
+) -> Result<Vec<ShortcutKV>, Error> {
+    let mut resolved = Vec::with_capacity(shortcuts.len());
+    for mut shortcut in shortcuts {
+        if !shortcut.literal
+            && !Path::new(&shortcut.value).is_absolute()
+            && shortcut.value.contains(['~', '$', '%'])
+        {
+            shortcut.literal = true;
+        }
+        if shortcut.scope.applies_to(machine) {
+            if !shortcut.literal {
+                let path = fs::to_absolute_path(Path::new(&shortcut.value))?;
+                shortcut.value = path.to_string_lossy().to_string();
+            }
+            let target = shortcut.target()?;
+            if !target.is_dir() {
+                let message = format!(
+                    "Skipping shortcut \"{}\", \"{}\" is not a directory",
+                    shortcut.key,
+                    target.display()
+                );
+                eprintln!("{}", message.yellow());
+                continue;
+            }
+        }
+        resolved.push(shortcut);
+    }
+    Ok(resolved)
+}
+
+/// Proposes a shortcut for every directory that does not have one yet. Keys are derived from the
+/// directory name, prefixed by the parent directory name or suffixed by a number when the key is
+/// already taken.
+pub fn propose_shortcuts(config: &Config, dirs: &[ImportedDir]) -> Vec<ShortcutKV> {
+    let mut taken_keys: HashSet<String> = config.shortcuts.iter().map(|x| x.key.clone()).collect();
+    let mut known_targets: HashSet<&str> = config.shortcuts.iter().map(|x| &x.value[..]).collect();
+    let mut proposals = Vec::new();
+    for dir in dirs {
+        if !known_targets.insert(&dir.path) {
+            continue;
+        }
+        let path = Path::new(&dir.path);
+        let name = match path.file_name() {
+            Some(name) => to_key(&name.to_string_lossy()),
+            None => continue,
+        };
+        let mut candidates = vec![name.clone()];
+        if let Some(parent_name) = path.parent().and_then(|x| x.file_name()) {
+            candidates.push(format!(
+                "{}-{}",
+                to_key(&parent_name.to_string_lossy()),
+                name
+            ));
+        }
+        let key = match candidates.into_iter().find(|x| !taken_keys.contains(x)) {
+            Some(key) => key,
+            None => (2..)
+                .map(|i| format!("{}{}", name, i))
+                .find(|x| !taken_keys.contains(x))
+                .expect("There are finitely many taken keys"),
+        };
+        taken_keys.insert(key.clone());
+        proposals.push(ShortcutKV::new(key, dir.path.clone()));
+    }
+    proposals
+}
+
+/// Lowercase directory name with whitespace replaced by dashes.
+fn to_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join("-")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(key: &str, value: &str) -> ShortcutKV {
+        ShortcutKV::new(key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_export_import() {
+        let shortcuts = vec![
+            shortcut("dl", "/home/user/Downloads"),
+            shortcut("odd", "/tmp/it's a \"dir\", with $HOME"),
+            shortcut("multi", "/tmp/line\nbreak"),
+        ];
+        for (format, extension) in [
+            (FileFormat::Json, "json"),
+            (FileFormat::Csv, "csv"),
+            (FileFormat::BashAliases, "sh"),
+        ] {
+            assert_eq!(
+                Some(format),
+                FileFormat::from_path(Path::new(&format!("shortcuts.{}", extension)))
+            );
+            let exported = format.export(&shortcuts);
+            let (imported, expected) = match format {
+                FileFormat::Json => (json::import(&exported), &shortcuts[..]),
+                FileFormat::Csv => (csv::import(&exported), &shortcuts[..]),
+                // Values with line breaks are not exported as aliases.
+                _ => (aliases::import(&exported), &shortcuts[..2]),
+            };
+            assert_eq!(Ok(expected.to_vec()), imported, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_export_import_scope() {
+        let mut windows = shortcut("repo", "D:\\code");
+        windows.scope.os = Some("windows".to_string());
+        let mut laptop = shortcut("repo", "/srv/code");
+        laptop.scope.host = Some("laptop".to_string());
+        let shortcuts = vec![shortcut("repo", "/code"), windows, laptop];
+        let exported = FileFormat::Csv.export(&shortcuts);
+        assert!(exported.starts_with("key,value,host,os\r\nrepo,/code,,\r\n"));
+        assert_eq!(Ok(shortcuts.clone()), csv::import(&exported));
+        let exported = FileFormat::Json.export(&shortcuts);
+        assert_eq!(Ok(shortcuts), json::import(&exported));
+
+        // Files with only keys and values are used on every machine.
+        let expected = vec![shortcut("repo", "/code")];
+        assert_eq!(Ok(expected), csv::import("key,value\nrepo,/code\n"));
+        assert!(csv::import("repo,/code,laptop\n").is_err());
+    }
+
+    #[test]
+    fn test_export_literal_alias() {
+        let mut downloads = shortcut("dl", "~/Downloads");
+        downloads.literal = true;
+        let target = downloads.target().unwrap().to_string_lossy().into_owned();
+        let exported = FileFormat::BashAliases.export(&[downloads]);
+        assert!(!exported.contains('~'));
+        assert_eq!(
+            Ok(vec![shortcut("dl", &target)]),
+            aliases::import(&exported)
+        );
+    }
+
+    #[test]
+    fn test_import_bash_aliases() {
+        let content =
+            "alias ll='ls -l'\nalias dl=\"cd ~/Downloads\"\nalias w='cd \"/mnt/my work\"'\n";
+        let expected = vec![shortcut("dl", "~/Downloads"), shortcut("w", "/mnt/my work")];
+        assert_eq!(Ok(expected), aliases::import(content));
+    }
+
+    #[test]
+    fn test_resolve_targets() {
+        let machine = Machine {
+            host: "laptop".to_string(),
+            os: "linux".to_string(),
+        };
+        let cwd = std::env::current_dir().unwrap();
+        let content = "alias home='cd ~'\nalias src='cd src'\nalias gone='cd /does/not/exist'\n";
+        let imported = aliases::import(content).unwrap();
+        let resolved = resolve_targets(imported, &machine).unwrap();
+        let mut home = shortcut("home", "~");
+        home.literal = true;
+        let src = shortcut("src", &cwd.join("src").to_string_lossy());
+        assert_eq!(vec![home, src], resolved);
+
+        // Targets of other machines are kept as they are.
+        let csv = "key,value\nrepo,D:\\code\n";
+        let mut imported = csv::import(csv).unwrap();
+        imported[0].scope.os = Some("windows".to_string());
+        let resolved = resolve_targets(imported.clone(), &machine).unwrap();
+        assert_eq!(imported, resolved);
+        let mut imported = csv::import("key,value\nrel,src\nenv,$HOME\n").unwrap();
+        let resolved = resolve_targets(imported.clone(), &machine).unwrap();
+        imported[0].value = cwd.join("src").to_string_lossy().to_string();
+        imported[1].literal = true;
+        assert_eq!(imported, resolved);
+    }
+
+    #[test]
+    fn test_cdpath() {
+        let shortcuts = vec![
+            shortcut("dl", "/home/user/Downloads"),
+            shortcut("c", "/code"),
+        ];
+        let exported = FileFormat::Cdpath.export(&shortcuts);
+        let dirs: Vec<String> = cdpath::import(&exported)
+            .expect("Should import")
+            .into_iter()
+            .map(|x| x.path)
+            .collect();
+        assert_eq!(vec!["/home/user/Downloads", "/code"], dirs);
+    }
+
+    fn dir(path: &str) -> ImportedDir {
+        ImportedDir {
+            path: path.to_string(),
+            score: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_propose_shortcuts() {
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![ShortcutKV::new(
+                "src".to_string(),
+                "/home/user/shortcut/src".to_string(),
+            )],
+        };
+        let dirs = vec![
+            dir("/home/user/shortcut/src"),
+            dir("/home/user/other/src"),
+            dir("/home/user/My Documents"),
+            dir("/work/other/src"),
+            dir("/"),
+        ];
+        let proposals: Vec<(String, String)> = propose_shortcuts(&config, &dirs)
+            .into_iter()
+            .map(|x| (x.key, x.value))
+            .collect();
+        let expected: Vec<(String, String)> = vec![
+            ("other-src", "/home/user/other/src"),
+            ("my-documents", "/home/user/My Documents"),
+            ("src2", "/work/other/src"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(expected, proposals);
+    }
+}
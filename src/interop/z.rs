@@ -0,0 +1,57 @@
+//! z (https://github.com/rupa/z) and fasd (https://github.com/clvv/fasd) share the same database
+//! format, one `path|rank|time` line per directory.
+
+use {
+    super::ImportedDir,
+    crate::{error::Error, fs},
+    std::path::PathBuf,
+};
+
+pub fn default_database(env_var: &str, default: &str) -> Result<PathBuf, Error> {
+    match std::env::var_os(env_var) {
+        Some(database) if !database.is_empty() => Ok(PathBuf::from(database)),
+        _ => fs::to_absolute_path(&PathBuf::from(default)),
+    }
+}
+
+pub fn parse(lines: &[String]) -> Result<Vec<ImportedDir>, String> {
+    let mut dirs = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Paths may contain '|', so the rank and time are taken from the end of the line.
+        let mut fields = line.rsplitn(3, '|');
+        let _time = fields.next();
+        let rank = fields.next().and_then(|x| x.trim().parse::<f64>().ok());
+        match (rank, fields.next()) {
+            (Some(rank), Some(path)) => dirs.push(ImportedDir {
+                path: path.to_string(),
+                score: rank,
+            }),
+            _ => return Err(format!("Invalid line {}: \"{}\"", i + 1, line)),
+        }
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let lines: Vec<String> = vec!["/home/user/code|42.5|1700000000", "/tmp/a|b|3|1700000001"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let dirs = parse(&lines).expect("Should parse");
+        assert_eq!(2, dirs.len());
+        assert_eq!("/home/user/code", dirs[0].path);
+        assert_eq!(42.5, dirs[0].score);
+        assert_eq!("/tmp/a|b", dirs[1].path);
+
+        let lines = vec!["/home/user/code".to_string()];
+        assert!(parse(&lines).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! zoxide (https://github.com/ajeetdsouza/zoxide) stores its database in `db.zo`, a bincode
+//! encoded version number followed by the list of directories.
+
+use {
+    super::ImportedDir,
+    crate::{error::Error, fs},
+    directories::BaseDirs,
+    std::path::PathBuf,
+};
+
+const SUPPORTED_VERSION: u32 = 3;
+
+pub fn default_database() -> Result<PathBuf, Error> {
+    let data_dir = match std::env::var_os("_ZO_DATA_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let base_dirs = BaseDirs::new().ok_or(Error::NoHomeDirectory)?;
+            base_dirs.data_local_dir().join("zoxide")
+        }
+    };
+    fs::to_absolute_path(&data_dir.join("db.zo"))
+}
+
+/// Reads little endian fixed size integers and length prefixed strings, as encoded by bincode.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err("Unexpected end of file".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?.try_into().expect("Took 4 bytes");
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        let bytes = self.take(8)?.try_into().expect("Took 8 bytes");
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        let bytes = self.take(8)?.try_into().expect("Took 8 bytes");
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u64()? as usize;
+        match std::str::from_utf8(self.take(len)?) {
+            Ok(value) => Ok(value.to_string()),
+            Err(err) => Err(format!("Invalid UTF-8 path: {}", err)),
+        }
+    }
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Vec<ImportedDir>, String> {
+    if bytes.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut reader = Reader { bytes };
+    let version = reader.u32()?;
+    if version != SUPPORTED_VERSION {
+        return Err(format!(
+            "Unsupported zoxide database version {}, expected {}",
+            version, SUPPORTED_VERSION
+        ));
+    }
+    let len = reader.u64()?;
+    let mut dirs = Vec::new();
+    for _ in 0..len {
+        let path = reader.string()?;
+        let rank = reader.f64()?;
+        let _last_accessed = reader.u64()?;
+        dirs.push(ImportedDir { path, score: rank });
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend(SUPPORTED_VERSION.to_le_bytes());
+        bytes.extend(2u64.to_le_bytes());
+        for (path, rank) in [("/home/user/code", 12.0f64), ("/tmp", 1.5f64)] {
+            bytes.extend((path.len() as u64).to_le_bytes());
+            bytes.extend(path.as_bytes());
+            bytes.extend(rank.to_le_bytes());
+            bytes.extend(1700000000u64.to_le_bytes());
+        }
+        let dirs = parse(&bytes).expect("Should parse");
+        assert_eq!(2, dirs.len());
+        assert_eq!("/home/user/code", dirs[0].path);
+        assert_eq!(1.5, dirs[1].score);
+
+        assert!(parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
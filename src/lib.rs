@@ -1,137 +1,1044 @@
 use {
-    colored::Colorize,
-    config::{ConfigAddResult, ConfigRemoveResult},
-    shell::{Bash, CommandPrompt, PowerShell, Shell},
-    std::path::{Path, PathBuf},
+    colored::{ColoredString, Colorize},
+    config::{
+        Config, ConfigAddResult, ConfigRemoveResult, ConflictPolicy, Machine, MatchKind, Scope,
+        ShortcutKV, fuzzy_score,
+        history::{History, HistoryEntry},
+        project::ProjectShortcuts,
+    },
+    interop::{FileFormat, ImportSource},
+    shell::{SetupOptions, SetupStatus, Shell, ShellRegistry, ShellSelection},
+    std::path::{Component, Path, PathBuf},
 };
 
 pub mod config;
+mod error;
 pub mod fs;
+pub mod interop;
+mod picker;
 pub mod shell;
 
-pub fn setup(command: String, path_location: Option<PathBuf>) -> Result<(), String> {
-    let config = config::create_config(&command, path_location)?;
+pub use error::Error;
 
-    // TODO: Use macro_rules! to avoid repeating myself :)
-    // Bash
-    match Bash::new() {
-        Err(msg) => {
-            let msg = format!("Unexpected error looking for Bash: {}", msg);
-            eprintln!("{}", msg.red());
+/// Sets up the shells of `registry` chosen by `selection` that are installed, printing a summary
+/// of what was done with each of them. With `dry_run` nothing is written, a diff of every file that
+/// would be written is printed instead. Every shell is tried, the error of the first one that
+/// failed is returned.
+pub fn setup(
+    registry: &ShellRegistry,
+    selection: &ShellSelection,
+    command: String,
+    path_location: Option<PathBuf>,
+    options: &SetupOptions,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let mut selected = vec![];
+    for backend in registry.backends() {
+        selected.push(registry.is_selected(backend.id, selection)?);
+    }
+    let config = if dry_run {
+        config::get_setup_config(&command, path_location)?
+    } else {
+        config::create_config(&command, path_location)?
+    };
+    let mut summary: Vec<(&str, String)> = vec![];
+    let mut failures = vec![];
+    for (backend, selected) in registry.backends().iter().zip(selected) {
+        if !selected {
+            summary.push((backend.id, "skipped".dimmed().to_string()));
+            continue;
         }
-        Ok(shell) => {
-            shell.map(|x| x.configure(&config));
+        let result = match backend.find() {
+            Ok(Some(shell)) if dry_run => print_plan(shell.as_ref(), &config, options),
+            Ok(Some(shell)) => {
+                println!("Setting up {}", shell.name());
+                shell
+                    .try_configure(&config, options)
+                    .map(|_| "configured".green())
+            }
+            Ok(None) => Ok("not installed".dimmed()),
+            Err(err) => Err(err),
+        };
+        let status = result.unwrap_or_else(|err| {
+            let status = format!("failed: {}", err).red();
+            failures.push(err);
+            status
+        });
+        summary.push((backend.id, status.to_string()));
+    }
+    let width = summary.iter().map(|(id, _)| id.len()).max().unwrap_or(0);
+    println!("Summary:");
+    for (id, status) in summary {
+        println!("  {}{} {}", id, " ".repeat(width - id.len()), status);
+    }
+    if !failures.is_empty() {
+        let message = format!("{} shell(s) could not be set up", failures.len());
+        println!("{}", message.red());
+    }
+    if dry_run {
+        println!("Dry run, no file was written");
+    }
+    match failures.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Prints a diff and the warnings of every file `shell` would write during setup, returns the
+/// summary status of the shell: how many files would change, or that setup would be refused
+/// because a shortcut block was edited by hand.
+fn print_plan(
+    shell: &dyn Shell,
+    config: &Config,
+    options: &SetupOptions,
+) -> Result<ColoredString, Error> {
+    let mut changed = 0;
+    let mut edited = 0;
+    for file in shell.try_plan(config, options)? {
+        if let Some(warning) = &file.warning {
+            println!("{}", warning.yellow());
+        }
+        let diff = file.diff()?;
+        if diff.is_empty() {
+            println!("No changes to \"{}\"", file.path.display());
+            continue;
+        }
+        changed += 1;
+        print!("{}", diff);
+        if file.edited && !options.force {
+            edited += 1;
+            let message = format!(
+                "The shortcut block in \"{}\" was edited by hand, refused without --force",
+                file.path.display()
+            );
+            println!("{}", message.red());
         }
     }
+    Ok(if edited > 0 {
+        format!("{} edited file(s), refused without --force", edited).red()
+    } else if changed == 0 {
+        "up to date".dimmed()
+    } else {
+        format!("would change {} file(s)", changed).yellow()
+    })
+}
 
-    // Command prompt
-    match CommandPrompt::new() {
-        Err(msg) => {
-            let msg = format!("Unexpected error looking for Command Prompt: {}", msg);
-            eprintln!("{}", msg.red());
+/// Removes the setup of every shell of `registry`, and with `remove_config` the config file. Every
+/// shell is tried, the error of the first one that failed is returned.
+pub fn uninstall(registry: &ShellRegistry, remove_config: bool, force: bool) -> Result<(), Error> {
+    let config = match config::get_config() {
+        Err(Error::ConfigNotFound) => {
+            let message = format!(
+                "Config file not found, uninstalling the default \"{}\" command",
+                config::DEFAULT_COMMAND
+            );
+            println!("{}", message.yellow());
+            config::get_setup_config(config::DEFAULT_COMMAND, None)?
+        }
+        result => result?,
+    };
+    let options = SetupOptions {
+        force,
+        ..SetupOptions::default()
+    };
+    let mut failure = None;
+    // Every backend, even the ones no longer installed, so removing a shell before shortcut does
+    // not leave its setup behind.
+    for backend in registry.backends() {
+        let result = match backend.locate() {
+            Err(err) => {
+                let msg = format!("Unexpected error looking for {}, {}", backend.id, err);
+                eprintln!("{}", msg.red());
+                Err(err)
+            }
+            Ok(Some(shell)) => shell.unconfigure(&config, &options),
+            Ok(None) => Ok(()),
+        };
+        if let Err(err) = result {
+            failure.get_or_insert(err);
+        }
+    }
+    if remove_config {
+        match config::delete_config()? {
+            Some(config_file) => println!("Deleted config file \"{}\"", config_file.display()),
+            None => println!("Config file was already deleted"),
         }
-        Ok(shell) => {
-            shell.map(|x| x.configure(&config));
+    }
+    match failure {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Puts back the latest backup of every profile of the shell `id`, undoing the last time setup or
+/// uninstall changed it. The backup is consumed, so restoring again goes one change further back.
+pub fn restore_profile(registry: &ShellRegistry, id: &str) -> Result<(), Error> {
+    let backend = registry.get(id)?;
+    let shell = match backend.locate()? {
+        Some(shell) => shell,
+        None => {
+            return Err(Error::Shell {
+                shell: backend.id,
+                message: "Not installed".to_string(),
+            });
         }
     };
+    // Not `try_plan`, the profile is not parsed so a mangled shortcut block can be rolled back.
+    let mut restored = 0;
+    for profile in shell.profiles()? {
+        match fs::find_latest_backup(&profile)? {
+            Some(backup) => {
+                fs::rename(&backup, &profile)?;
+                restored += 1;
+                println!(
+                    "Restored \"{}\" from \"{}\"",
+                    profile.display(),
+                    backup.display()
+                );
+            }
+            None => println!("No backup of \"{}\"", profile.display()),
+        }
+    }
+    if restored == 0 {
+        println!("Nothing to restore for {}", shell.name());
+    }
+    Ok(())
+}
 
-    // Power shell
-    match PowerShell::new() {
-        Err(msg) => {
-            let msg = format!("Unexpected error looking for Command Prompt: {}", msg);
-            eprintln!("{}", msg.red());
+/// Prints the script defining the command in the shell `id`, to be loaded at startup, e.g. with
+/// `eval "$(shortcut init bash)"`.
+pub fn init(registry: &ShellRegistry, id: &str) -> Result<(), Error> {
+    let backend = registry.get(id)?;
+    let init = match backend.init {
+        Some(init) => init,
+        None => {
+            return Err(Error::Shell {
+                shell: backend.id,
+                message: "The script can not be loaded with init, run `shortcut setup`".to_string(),
+            });
         }
-        Ok(shell) => {
-            shell.map(|x| x.configure(&config));
+    };
+    let config = config::get_config()?;
+    for line in init(&config) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Looks for every shell of `registry` installed, unexpected errors are reported and the shell is
+/// skipped.
+fn find_shells(registry: &ShellRegistry) -> Vec<Box<dyn Shell>> {
+    let mut shells = vec![];
+    for backend in registry.backends() {
+        match backend.find() {
+            Err(err) => {
+                let msg = format!("Unexpected error looking for {}, {}", backend.id, err);
+                eprintln!("{}", msg.red());
+            }
+            Ok(Some(shell)) => shells.push(shell),
+            Ok(None) => (),
         }
     }
+    shells
+}
+
+/// Output format of `list`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListFormat {
+    /// Human readable table.
+    Table,
+    /// JSON object with the `command`, `path_location` and `shortcuts` fields. Every shortcut
+    /// has a `source`, `global` or the path of the project file, and whether it is `active` on
+    /// this machine. Shortcuts used on specific machines have `host` and `os` fields.
+    Json,
+    /// Tab separated lines: `command`, `path_location` and one `shortcut` line per shortcut
+    /// followed by its key, target, source, host, OS and whether it is active (`true` or
+    /// `false`). Host and OS are empty for shortcuts used on every machine. Tabs, line breaks and
+    /// backslashes in values are escaped.
+    Tsv,
+    /// Null terminated fields: the command, the path location (empty if not set) and then the
+    /// key, target, source, host, OS and active fields of every shortcut, as in `Tsv`.
+    NullSeparated,
+}
 
+pub fn list(format: ListFormat) -> Result<(), Error> {
+    let config = config::get_config()?;
+    let project = config::project::get_project_shortcuts()?;
+    match format {
+        ListFormat::Table => print_table(&config, project.as_ref()),
+        ListFormat::Json => println!("{}", format_json(&config, project.as_ref())),
+        ListFormat::Tsv => {
+            for line in format_tsv(&config, project.as_ref()) {
+                println!("{}", line);
+            }
+        }
+        ListFormat::NullSeparated => {
+            let path_location = config.path_location.as_deref().unwrap_or_default();
+            print!("{}\0{}\0", config.command, path_location);
+            for fields in get_fields(&config, project.as_ref()) {
+                for field in fields {
+                    print!("{}\0", field);
+                }
+            }
+        }
+    }
     Ok(())
 }
 
-pub fn list() -> Result<(), String> {
-    let mut config = config::get_config()?;
+/// Where a listed shortcut comes from and whether it is used on this machine.
+struct Source {
+    /// `global` or the path of the project file.
+    name: String,
+    /// False for shortcuts of other machines and shortcuts hidden by another shortcut.
+    active: bool,
+}
+
+/// Every shortcut of the project and the config, in that order, with their source.
+fn get_sources<'a>(
+    config: &'a Config,
+    project: Option<&'a ProjectShortcuts>,
+) -> Vec<(&'a ShortcutKV, Source)> {
+    let layered = match project {
+        Some(project) => config.clone().with_project(project),
+        None => config.clone(),
+    };
+    let active = layered.active_shortcuts(&Machine::current());
+    let mut sources = Vec::new();
+    if let Some(project) = project {
+        let name = project.file.to_string_lossy();
+        for shortcut in &project.shortcuts {
+            let active = active.contains(&shortcut);
+            let name = name.to_string();
+            sources.push((shortcut, Source { name, active }));
+        }
+    }
+    for shortcut in &config.shortcuts {
+        // Global shortcuts with the key of a project shortcut are hidden by it.
+        let hidden = sources.iter().any(|(x, _)| x.key == shortcut.key);
+        let active = !hidden && active.contains(&shortcut);
+        let name = "global".to_string();
+        sources.push((shortcut, Source { name, active }));
+    }
+    sources
+}
+
+fn format_json(config: &Config, project: Option<&ProjectShortcuts>) -> serde_json::Value {
+    let shortcuts: Vec<serde_json::Value> = get_sources(config, project)
+        .into_iter()
+        .map(|(shortcut, source)| {
+            let mut json = serde_json::json!(shortcut);
+            json["source"] = serde_json::json!(source.name);
+            json["active"] = serde_json::json!(source.active);
+            json
+        })
+        .collect();
+    serde_json::json!({
+        "command": config.command,
+        "path_location": config.path_location,
+        "shortcuts": shortcuts,
+    })
+}
+
+fn format_tsv(config: &Config, project: Option<&ProjectShortcuts>) -> Vec<String> {
+    let path_location = config.path_location.as_deref().unwrap_or_default();
+    let mut lines = vec![
+        format!("command\t{}", escape_tsv(&config.command)),
+        format!("path_location\t{}", escape_tsv(path_location)),
+    ];
+    for fields in get_fields(config, project) {
+        let fields: Vec<String> = fields.iter().map(|x| escape_tsv(x)).collect();
+        lines.push(format!("shortcut\t{}", fields.join("\t")));
+    }
+    lines
+}
+
+/// Key, target, source, host, OS and active fields of every shortcut, for the line based formats.
+fn get_fields(config: &Config, project: Option<&ProjectShortcuts>) -> Vec<[String; 6]> {
+    get_sources(config, project)
+        .into_iter()
+        .map(|(shortcut, source)| {
+            [
+                shortcut.key.clone(),
+                shortcut.value.clone(),
+                source.name,
+                shortcut.scope.host.clone().unwrap_or_default(),
+                shortcut.scope.os.clone().unwrap_or_default(),
+                source.active.to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn escape_tsv(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn print_table(config: &Config, project: Option<&ProjectShortcuts>) {
     println!("Command: \"{}\"", config.command);
-    if config.shortcuts.len() == 0 {
+    if let Some(project) = project {
+        println!("Project file: \"{}\"", project.file.display());
+    }
+    let mut sources = get_sources(config, project);
+    if sources.is_empty() {
         println!("No shortcuts. See `shorcuts add --help` for instructions.");
-        return Ok(());
+        return;
     }
-    println!("Shortcuts ({}):", config.shortcuts.len());
-    config.shortcuts.sort_by(|x, y| {
+    println!("Shortcuts ({}):", sources.len());
+    sources.sort_by(|(x, _), (y, _)| {
         let a = x.value.to_lowercase();
         let b = y.value.to_lowercase();
         a.cmp(&b)
     });
-    let width = config
-        .shortcuts
+    let width = sources
         .iter()
-        .max_by(|x, y| {
-            let a = x.key.len();
-            let b = y.key.len();
-            a.cmp(&b)
-        })
-        .expect("Already asserted shortcuts list is not empty")
-        .key
-        .len();
-    for shortcut in &config.shortcuts {
-        let spaces = String::from_utf8(vec![' ' as u8; 1 + width - shortcut.key.len()])
-            .expect("String of 1 or more spaces must be a valid utf-8");
-        let text = format!("  {}{}{}", shortcut.key, spaces, shortcut.value);
-        println!("{}", text);
+        .map(|(shortcut, _)| shortcut.key.len())
+        .max()
+        .expect("Already asserted shortcuts list is not empty");
+    // Shortcuts from the project file are marked as `project`, global shortcuts they hide and
+    // variants of a key for other machines are dimmed.
+    for (shortcut, source) in &sources {
+        let spaces = " ".repeat(1 + width - shortcut.key.len());
+        let source_name = if source.name == "global" {
+            "global "
+        } else {
+            "project"
+        };
+        let mut text = format!(
+            "  {}{}{} {}",
+            shortcut.key, spaces, source_name, shortcut.value
+        );
+        if !shortcut.scope.is_global() {
+            text.push_str(&format!(" ({})", shortcut.scope));
+        }
+        if source.active {
+            println!("{}", text);
+        } else {
+            println!("{}", text.dimmed());
+        }
+    }
+}
+
+/// Adds a shortcut to `target` used on the machines in `scope`. With `literal` the target is
+/// stored as given, e.g. `~/Downloads` or `$HOME/Downloads`, and expanded every time it is used
+/// instead of storing its absolute path. Targets for other machines are always stored as given.
+pub fn add(key: String, target: PathBuf, literal: bool, scope: Scope) -> Result<(), Error> {
+    let literal = literal || !scope.applies_to(&Machine::current());
+    let target = if literal {
+        target
+    } else {
+        fs::to_absolute_path(&target)?
+    };
+    let shortcut = ShortcutKV {
+        key,
+        value: target.to_string_lossy().into_owned(),
+        scope,
+        literal,
+    };
+    let add_result = config::add_shortcut(shortcut.clone())?;
+    print_add_result(&shortcut, add_result);
+    Ok(())
+}
+
+/// Imports the `top` highest ranked directories from the database of another tool, adding a
+/// shortcut for each directory that does not have one yet.
+pub fn import_history(
+    source: ImportSource,
+    database: Option<PathBuf>,
+    top: Option<usize>,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let database = match database {
+        Some(database) => fs::to_absolute_path(&database)?,
+        None => source.default_database()?,
+    };
+    println!("Reading {} database \"{}\"", source, database.display());
+    let mut dirs = source.read_database(&database)?;
+    dirs.retain(|x| Path::new(&x.path).is_dir());
+    if let Some(top) = top {
+        dirs.truncate(top);
+    }
+    let config = config::get_config()?;
+    let proposals = interop::propose_shortcuts(&config, &dirs);
+    if proposals.is_empty() {
+        println!("Nothing to import, all directories already have a shortcut.");
+        return Ok(());
+    }
+    if dry_run {
+        println!("Would add {} shortcuts:", proposals.len());
+        for shortcut in &proposals {
+            println!("  {} -> {}", shortcut.key, shortcut.value);
+        }
+        return Ok(());
+    }
+    let add_results = config::merge_shortcuts(&proposals, ConflictPolicy::Rename)?;
+    for (shortcut, add_result) in proposals.iter().zip(add_results) {
+        print_add_result(shortcut, add_result);
+    }
+    Ok(())
+}
+
+/// Writes all the shortcuts in the given format to `output`, or to stdout if not given.
+pub fn export(format: FileFormat, output: Option<PathBuf>) -> Result<(), Error> {
+    let config = config::get_config()?;
+    let content = match format {
+        // Aliases and `CDPATH` are sourced on this machine, only the variants used on it are kept.
+        FileFormat::BashAliases | FileFormat::Cdpath => {
+            let active: Vec<ShortcutKV> = config
+                .active_shortcuts(&Machine::current())
+                .into_iter()
+                .cloned()
+                .collect();
+            format.export(&active)
+        }
+        FileFormat::Json | FileFormat::Csv => format.export(&config.shortcuts),
+    };
+    match output {
+        Some(output) => fs::write_str(&fs::to_absolute_path(&output)?, &content),
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+/// Imports the shortcuts in a file written by `export`, the format is guessed from the file
+/// extension when not given.
+pub fn import_file(
+    file: PathBuf,
+    format: Option<FileFormat>,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let file = fs::to_absolute_path(&file)?;
+    let format = match format.or_else(|| FileFormat::from_path(&file)) {
+        Some(format) => format,
+        None => {
+            return Err(Error::InvalidImport {
+                path: file,
+                reason: "Unknown file format, use --format".to_string(),
+            });
+        }
+    };
+    let mut config = config::get_config()?;
+    let shortcuts = format.import(&file, &config)?;
+    if dry_run {
+        // Merge into the config read, without writing it, to preview the changes.
+        for shortcut in shortcuts {
+            let add_result = config.merge(shortcut.clone(), policy)?;
+            print!("[dry run] ");
+            print_merge_result(&shortcut, add_result);
+        }
+        return Ok(());
+    }
+    let add_results = config::merge_shortcuts(&shortcuts, policy)?;
+    for (shortcut, add_result) in shortcuts.iter().zip(add_results) {
+        print_merge_result(shortcut, add_result);
     }
     Ok(())
 }
 
-pub fn add(key: String, target: PathBuf) -> Result<(), String> {
-    let target = fs::to_absolute_path(&target)?;
-    let add_result = config::add_shortcut(&key, &target)?;
+fn print_merge_result(shortcut: &ShortcutKV, add_result: ConfigAddResult) {
     match add_result {
-        ConfigAddResult::NoChange => println!(
-            "Nothing done, shortcut already exists: {} -> {}",
-            key,
-            target.display()
-        ),
-        ConfigAddResult::Created(sc) => {
-            println!("Successfully added shortcut: {} -> {}", sc.key, sc.value)
+        ConfigAddResult::NoChange => {
+            println!("Skipped shortcut, key already exists: {}", shortcut)
+        }
+        ConfigAddResult::Created(sc) if sc.key != shortcut.key => {
+            println!("Renamed shortcut {} to {}: {}", shortcut.key, sc.key, sc)
         }
+        add_result => print_add_result(shortcut, add_result),
+    }
+}
+
+fn print_add_result(shortcut: &ShortcutKV, add_result: ConfigAddResult) {
+    match add_result {
+        ConfigAddResult::NoChange => {
+            println!("Nothing done, shortcut already exists: {}", shortcut)
+        }
+        ConfigAddResult::Created(sc) => println!("Successfully added shortcut: {}", sc),
         ConfigAddResult::Updated(existing, added) => {
             println!("Successfully updated shortcut.");
-            println!(
-                "Existing shortcut was: {} -> {}",
-                existing.key, existing.value
-            );
-            println!("New shortcut is: {} -> {}", added.key, added.value);
+            println!("Existing shortcut was: {}", existing);
+            println!("New shortcut is: {}", added);
         }
     };
-    Ok(())
 }
 
-pub fn remove(key: String) -> Result<(), String> {
-    let remove_result = config::remove_shortcut(&key)?;
+/// Removes the shortcut with the given key used on the machines in `scope`.
+pub fn remove(key: String, scope: Scope) -> Result<(), Error> {
+    let remove_result = config::remove_shortcut(&key, &scope)?;
     match remove_result {
-        ConfigRemoveResult::NotFound => println!("Did not find any shortcut for key \"{}\"", key),
+        ConfigRemoveResult::NotFound if scope.is_global() => {
+            println!("Did not find any shortcut for key \"{}\"", key)
+        }
+        ConfigRemoveResult::NotFound => {
+            println!("Did not find any shortcut for key \"{}\" on {}", key, scope)
+        }
         ConfigRemoveResult::Removed(removed) => {
-            println!(
-                "Successfully removed shortcut {} -> {}",
-                removed.key, removed.value
-            )
+            println!("Successfully removed shortcut {}", removed)
         }
     }
     Ok(())
 }
 
-pub fn get(key: String) -> Result<(), String> {
-    let config = config::get_config()?;
+pub fn get(key: String) -> Result<(), Error> {
+    let config = get_layered_config()?;
     if Path::new(&key).is_dir() {
         println!("{}", key);
         return Ok(());
     }
-    for shortcut in &config.shortcuts {
-        if key == shortcut.key {
-            println!("{}", shortcut.value.clone());
-            return Ok(());
+    match find_target(
+        &config,
+        config::history::get_history,
+        &key,
+        config::history::now(),
+    )? {
+        Some(target) => println!("{}", target.display()),
+        None => println!("{}", key),
+    }
+    Ok(())
+}
+
+/// Where `get` goes for `key`: the shortcut it resolves to, or the visited directory with the
+/// highest frecency whose name contains `key` when no shortcut matches. A visited directory named
+/// by `key`, or by a name starting with it, wins over a shortcut that only matches fuzzily with a
+/// lower score. The history is only loaded when no shortcut is an exact or prefix match.
+fn find_target(
+    config: &Config,
+    get_history: impl FnOnce() -> Result<History, Error>,
+    key: &str,
+    now: u64,
+) -> Result<Option<PathBuf>, Error> {
+    let is_visited_dir = |entry: &&HistoryEntry| Path::new(&entry.path).is_dir();
+    match resolve(config, key)? {
+        Some((target, MatchKind::Fuzzy(score))) => {
+            let history = get_history()?;
+            let visited = history
+                .find_by_prefix(key, now)
+                .filter(is_visited_dir)
+                .filter(|entry| history_score(key, entry) > Some(score));
+            match visited {
+                Some(entry) => Ok(Some(PathBuf::from(&entry.path))),
+                None => Ok(Some(target)),
+            }
         }
+        Some((target, _)) => Ok(Some(target)),
+        None => Ok(get_history()?
+            .find(key, now)
+            .filter(is_visited_dir)
+            .map(|entry| PathBuf::from(&entry.path))),
+    }
+}
+
+/// `fuzzy_score` of `key` against the name of a visited directory.
+fn history_score(key: &str, entry: &HistoryEntry) -> Option<i64> {
+    Path::new(&entry.path)
+        .file_name()
+        .and_then(|name| fuzzy_score(key, &name.to_string_lossy()))
+}
+
+/// Checks that every shortcut target is an existing directory, that every shell found that was set
+/// up has an up to date setup and that the Command Prompt script directory is in `PATH`. With `fix`
+/// broken shortcuts are re-pointed to a visited directory with the same name, or removed if there
+/// is none, and outdated shells are set up again, keeping whether their profiles load the script
+/// with `shortcut init`. Fails when problems are found without `fix`, or with the error of the
+/// first shell that could not be set up again.
+pub fn doctor(registry: &ShellRegistry, fix: bool) -> Result<(), Error> {
+    let config = config::get_config()?;
+    let mut problems = 0;
+    let mut failure = None;
+
+    println!("Checking shortcuts");
+    let history = config::history::get_history()?;
+    let now = config::history::now();
+    let machine = Machine::current();
+    // Targets of shortcuts used on other machines can not be checked here.
+    for shortcut in config
+        .shortcuts
+        .iter()
+        .filter(|x| x.scope.applies_to(&machine))
+    {
+        let target = shortcut.target()?;
+        let problem = if !target.exists() {
+            "does not exist"
+        } else if !target.is_dir() {
+            "is not a directory"
+        } else {
+            continue;
+        };
+        problems += 1;
+        let message = format!("  {}: target {}", shortcut, problem);
+        println!("{}", message.red());
+        if !fix {
+            continue;
+        }
+        let moved_to = target
+            .file_name()
+            .and_then(|name| history.find_by_name(&name.to_string_lossy(), now));
+        match moved_to {
+            Some(entry) => {
+                config::add_shortcut(ShortcutKV {
+                    value: entry.path.clone(),
+                    literal: false,
+                    ..shortcut.clone()
+                })?;
+                println!("    Re-pointed {} to \"{}\"", shortcut.key, entry.path);
+            }
+            None => {
+                config::remove_shortcut(&shortcut.key, &shortcut.scope)?;
+                println!("    Removed {}", shortcut.key);
+            }
+        }
+    }
+
+    println!("Checking shells");
+    for shell in find_shells(registry) {
+        let statuses = match shell.try_check(&config) {
+            Ok(statuses) => statuses,
+            Err(err) => {
+                problems += 1;
+                println!(
+                    "{}",
+                    format!("  Error checking {}: {}", shell.name(), err).red()
+                );
+                continue;
+            }
+        };
+        // Shells that were not set up, e.g. excluded with `setup --shell`, are left alone.
+        if statuses
+            .iter()
+            .all(|x| matches!(x, SetupStatus::Missing(_)))
+        {
+            println!("  {} is not set up", shell.name());
+            continue;
+        }
+        let mut outdated = false;
+        for status in statuses {
+            if status.is_up_to_date() {
+                println!("  {}", status);
+            } else {
+                outdated = true;
+                println!("{}", format!("  {}", status).red());
+            }
+        }
+        if outdated {
+            problems += 1;
+            if fix {
+                // Profiles loading the script with `shortcut init` keep doing so.
+                let options = SetupOptions {
+                    keep_mode: true,
+                    ..SetupOptions::default()
+                };
+                if let Err(err) = shell.configure(&config, &options) {
+                    failure.get_or_insert(err);
+                }
+            }
+        }
+    }
+
+    if let Some(path_location) = &config.path_location {
+        println!("Checking PATH");
+        let path_location = fs::to_absolute_path(Path::new(path_location))?;
+        let in_path = std::env::var_os("PATH").is_some_and(|path| {
+            std::env::split_paths(&path)
+                .any(|dir| fs::to_absolute_path(&dir).is_ok_and(|dir| dir == path_location))
+        });
+        if in_path {
+            println!("  \"{}\" is in PATH", path_location.display());
+        } else {
+            problems += 1;
+            let message = format!(
+                "  \"{}\" is not in PATH, Command Prompt will not find the {} command",
+                path_location.display(),
+                config.command
+            );
+            println!("{}", message.red());
+        }
+    }
+
+    match (problems, fix) {
+        (0, _) => println!("{}", "No problems found".green()),
+        (_, true) => println!("Found and tried to fix {} problems", problems),
+        (_, false) => return Err(Error::ProblemsFound(problems)),
+    }
+    match failure {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Lets the user pick a shortcut interactively and prints its target, nothing is printed if the
+/// user cancels.
+pub fn pick() -> Result<(), Error> {
+    let config = get_layered_config()?;
+    if let Some(shortcut) = picker::pick(&config)? {
+        println!("{}", shortcut.target()?.display());
     }
-    println!("{}", key);
     Ok(())
 }
+
+/// Records a visit to the given directory, used to rank directories for `get` when no shortcut
+/// matches.
+pub fn visit(dir: PathBuf) -> Result<(), Error> {
+    let dir = fs::to_absolute_path(&dir)?;
+    if !dir.is_dir() {
+        return Err(Error::NotADirectory(dir));
+    }
+    config::history::record_visit(&dir)
+}
+
+/// Prints the completions for a partially typed key, one per line, used by the shell completion
+/// functions installed during setup.
+pub fn complete(prefix: String) -> Result<(), Error> {
+    let config = get_layered_config()?;
+    for completion in completions(&config, &prefix) {
+        println!("{}", completion);
+    }
+    Ok(())
+}
+
+/// Candidates to complete `prefix` with. Before the first path separator these are the keys
+/// starting with `prefix`, after it they are the directories inside the shortcut target, e.g.
+/// `repo/sr` completes to `repo/src/`.
+fn completions(config: &Config, prefix: &str) -> Vec<String> {
+    let Some((head, partial)) = prefix.rsplit_once(['/', std::path::MAIN_SEPARATOR]) else {
+        let mut keys: Vec<String> = config
+            .active_shortcuts(&Machine::current())
+            .into_iter()
+            .filter(|shortcut| shortcut.key.starts_with(prefix))
+            .map(|shortcut| shortcut.key.clone())
+            .collect();
+        keys.sort();
+        return keys;
+    };
+    let dir = match resolve(config, head) {
+        Ok(Some((dir, _))) => dir,
+        _ => return vec![],
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut completions: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(partial))
+        .map(|name| format!("{}/{}/", head, name))
+        .collect();
+    completions.sort();
+    completions
+}
+
+/// The config with the shortcuts of the project file found from the current directory, if any,
+/// layered over it.
+fn get_layered_config() -> Result<Config, Error> {
+    let config = config::get_config()?;
+    match config::project::get_project_shortcuts()? {
+        Some(project) => Ok(config.with_project(&project)),
+        None => Ok(config),
+    }
+}
+
+/// Resolves `key` to a target directory. The first component of `key` is the shortcut key and
+/// the remaining components, if any, are a sub-path inside the shortcut target, e.g. for
+/// `repo/src/bin` the result is `src/bin` inside the target of `repo`. Returned with how the
+/// shortcut key was matched.
+fn resolve(config: &Config, key: &str) -> Result<Option<(PathBuf, MatchKind)>, Error> {
+    let mut components = Path::new(key).components();
+    let shortcut_key = match components.next() {
+        Some(Component::Normal(first)) => first.to_string_lossy(),
+        _ => return Ok(None),
+    };
+    let sub_path = components.as_path();
+    let (shortcut, kind) = match config.find(&shortcut_key)? {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+    if sub_path.as_os_str().is_empty() {
+        return Ok(Some((shortcut.target()?, kind)));
+    }
+    let target = shortcut.target()?.join(sub_path);
+    if !target.is_dir() {
+        return Err(Error::NotADirectory(target));
+    }
+    Ok(Some((target, kind)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_target() {
+        let dir = std::env::temp_dir().join(format!("shortcut-target-{}", std::process::id()));
+        for sub_dir in ["docker", "notes", "dotfiles"] {
+            fs::ensure_dir(&dir.join(sub_dir)).expect("Temp dir should be created");
+        }
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![
+                ShortcutKV::new("dl".to_string(), "/home/user/Downloads".to_string()),
+                ShortcutKV::new("dots".to_string(), "/home/user/dotfiles".to_string()),
+                ShortcutKV::new("drop".to_string(), "/home/user/dropbox-clock".to_string()),
+            ],
+        };
+        let now = 1000;
+        let mut history = History::default();
+        for sub_dir in ["docker", "notes"] {
+            history.visit(&dir.join(sub_dir).to_string_lossy(), now);
+        }
+        let target = |key: &str| find_target(&config, || Ok(history.clone()), key, now).unwrap();
+
+        // Exact and prefix shortcut matches win over visited directories, without reading them.
+        assert_eq!(Some(PathBuf::from("/home/user/Downloads")), target("dl"));
+        assert_eq!(Some(PathBuf::from("/home/user/dotfiles")), target("dot"));
+        let unreadable = || Err(Error::InvalidConfig("history".to_string()));
+        assert!(find_target(&config, unreadable, "dl", now).is_ok());
+        assert!(find_target(&config, unreadable, "dock", now).is_err());
+        // `dock` only matches `dropbox-clock` fuzzily, the visited `docker` is a better match.
+        assert_eq!(Some(dir.join("docker")), target("dock"));
+        assert_eq!(
+            Some(PathBuf::from("/home/user/dropbox-clock")),
+            target("dbc")
+        );
+        // No shortcut matches, the visited directory is used.
+        assert_eq!(Some(dir.join("notes")), target("note"));
+        assert_eq!(None, target("xyz"));
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
+
+    #[test]
+    fn test_list_formats() {
+        let laptop = Scope {
+            host: Some("laptop".to_string()),
+            os: Some("linux".to_string()),
+        };
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![
+                ShortcutKV::new("dl".to_string(), "/tmp/down\tloads".to_string()),
+                ShortcutKV {
+                    scope: laptop,
+                    ..ShortcutKV::new("repo".to_string(), "/home/user/repo".to_string())
+                },
+            ],
+        };
+        let json = format_json(&config, None);
+        let json_entries: Vec<Vec<String>> = json["shortcuts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| {
+                ["key", "value", "source", "host", "os", "active"]
+                    .iter()
+                    .map(|field| match &x[field] {
+                        serde_json::Value::String(value) => escape_tsv(value),
+                        serde_json::Value::Null => String::new(),
+                        value => value.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let tsv_entries: Vec<Vec<String>> = format_tsv(&config, None)
+            .iter()
+            .filter_map(|x| x.strip_prefix("shortcut\t"))
+            .map(|x| x.split('\t').map(String::from).collect())
+            .collect();
+        assert_eq!(2, tsv_entries.len());
+        assert_eq!(json_entries, tsv_entries);
+        assert_eq!(
+            vec!["dl", "/tmp/down\\tloads", "global", "", "", "true"],
+            tsv_entries[0]
+        );
+    }
+
+    /// A shell with a single profile and no setup, to test commands working on profiles.
+    struct ProfileShell {
+        profile: PathBuf,
+    }
+
+    impl Shell for ProfileShell {
+        fn name(&self) -> &'static str {
+            "Profile shell"
+        }
+
+        fn try_plan(&self, _: &Config, _: &SetupOptions) -> Result<Vec<shell::PlannedFile>, Error> {
+            Ok(vec![])
+        }
+
+        fn try_unconfigure(
+            &self,
+            _: &Config,
+            _: &SetupOptions,
+        ) -> Result<Vec<shell::Removed>, Error> {
+            Ok(vec![])
+        }
+
+        fn try_check(&self, _: &Config) -> Result<Vec<SetupStatus>, Error> {
+            Ok(vec![])
+        }
+
+        fn profiles(&self) -> Result<Vec<PathBuf>, Error> {
+            Ok(vec![self.profile.clone()])
+        }
+    }
+
+    #[test]
+    fn test_restore_broken_profile() {
+        let dir = std::env::temp_dir().join(format!("shortcut-restore-{}", std::process::id()));
+        fs::ensure_dir(&dir).expect("Temp dir should be created");
+        let profile = dir.join(".profile");
+        let original = vec!["export EDITOR=vi".to_string()];
+        fs::write_lines(&profile, &original).unwrap();
+        fs::backup_file(&profile).unwrap();
+        // A shortcut block without its end marker can not be parsed.
+        let broken = ["# ---------- shortcut start ----------", "s() {"].map(String::from);
+        fs::write_lines(&profile, &broken).unwrap();
+
+        let mut registry = ShellRegistry::empty();
+        let shell_profile = profile.clone();
+        registry.register("profile", move || {
+            Ok(Some(ProfileShell {
+                profile: shell_profile.clone(),
+            }))
+        });
+        restore_profile(&registry, "profile").unwrap();
+        assert_eq!(Some(original), fs::read_lines(&profile).unwrap());
+        assert_eq!(None, fs::find_latest_backup(&profile).unwrap());
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
+
+    #[test]
+    fn test_completions() {
+        let dir = std::env::temp_dir().join(format!("shortcut-complete-{}", std::process::id()));
+        for sub_dir in ["src/bin", "scripts", "docs"] {
+            fs::ensure_dir(&dir.join(sub_dir)).expect("Temp dir should be created");
+        }
+        let config = Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "s".to_string(),
+            shortcuts: vec![
+                ShortcutKV::new("repo".to_string(), dir.to_string_lossy().into_owned()),
+                ShortcutKV::new("re".to_string(), "/tmp".to_string()),
+                ShortcutKV::new("dl".to_string(), "/tmp".to_string()),
+            ],
+        };
+        assert_eq!(vec!["re", "repo"], completions(&config, "r"));
+        assert_eq!(vec!["dl", "re", "repo"], completions(&config, ""));
+        assert_eq!(
+            vec!["repo/scripts/", "repo/src/"],
+            completions(&config, "repo/s")
+        );
+        assert_eq!(vec!["repo/src/bin/"], completions(&config, "repo/src/"));
+        assert!(completions(&config, "unknown/s").is_empty());
+        std::fs::remove_dir_all(&dir).expect("Temp dir should be removed");
+    }
+}
@@ -1,6 +1,13 @@
 use {
-    clap::{Parser, Subcommand},
+    clap::{Args as ClapArgs, CommandFactory, Parser, Subcommand, ValueEnum},
+    clap_complete::Shell as CompletionShell,
     colored::Colorize,
+    shortcut::{
+        ListFormat,
+        config::{self, ConflictPolicy, Machine, Scope},
+        interop::{FileFormat, ImportSource},
+        shell::{SetupOptions, ShellRegistry, ShellSelection},
+    },
     std::path::PathBuf,
     std::process::ExitCode,
 };
@@ -26,12 +33,38 @@ enum Command {
     /// Do one-time setup of your shells to use shortcuts.
     Setup {
         /// Command used to change directory using the shortcuts.
-        #[arg(short, long, default_value_t=String::from("s"))]
+        #[arg(short, long, default_value_t=String::from(config::DEFAULT_COMMAND))]
         command: String,
         /// Need for Command Prompt setup only. Directory to place a .bat script,
         /// must be part of the PATH environment variable.
         #[arg(short, long)]
         path_location: Option<PathBuf>,
+        /// Only set up the given shells, comma separated, e.g. `--shell bash,zsh`. One of bash,
+        /// sh, zsh, fish, nu, cmd or powershell.
+        #[arg(long, value_delimiter = ',')]
+        shell: Vec<String>,
+        /// Do not set up the given shells, comma separated.
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+        /// Show a diff of every file setup would write without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+        /// Overwrite shortcut blocks in the profiles even if they were edited by hand.
+        #[arg(long)]
+        force: bool,
+        /// Only write to the profiles a line loading the command with `shortcut init`, so
+        /// upgrading shortcut also upgrades the command. Bash, sh, zsh and PowerShell only.
+        #[arg(long)]
+        init: bool,
+    },
+    /// Undoes the one-time setup, removing the shortcut command from your shells.
+    Uninstall {
+        /// Also delete the config file, including all the shortcuts.
+        #[arg(long)]
+        remove_config: bool,
+        /// Remove shortcut blocks from the profiles even if they were edited by hand.
+        #[arg(long)]
+        force: bool,
     },
     /// Adds a shortcut.
     /// After one-time setup you can do: $ {command} -a <KEY> <TARGET>
@@ -40,22 +73,226 @@ enum Command {
         key: String,
         /// Absolute or relative path to the target directory.
         target: PathBuf,
+        /// Store the target as given instead of its absolute path, quote it to keep `~` or
+        /// environment variables (`$HOME`, `${VAR}`, `${env:VAR}` or `%VAR%`) from being expanded
+        /// by your shell, e.g. `'~/Downloads'`. They are expanded every time the shortcut is used,
+        /// useful when the config is shared between machines.
+        #[arg(long)]
+        literal: bool,
+        #[command(flatten)]
+        scope: ScopeArgs,
     },
     /// Removes a shortcut.
     /// After one-time setup you can do: $ {command} -r <KEY>
     Remove {
         /// Shortcut key to remove
         key: String,
+        #[command(flatten)]
+        scope: ScopeArgs,
     },
     /// Lists all the existing shortcuts.
     /// After one-time setup you can do: $ {command} -l
-    List {},
+    List {
+        /// Output format, use any format other than table for scripts.
+        #[arg(short, long, value_enum, default_value_t = ListOutput::Table)]
+        format: ListOutput,
+    },
+    /// Checks for shortcuts whose target directory no longer exists and shells whose setup is
+    /// missing or outdated. Exits with code 10 if it finds problems without --fix.
+    Doctor {
+        /// Re-point broken shortcuts to a visited directory with the same name, or remove them,
+        /// and set up outdated shells again.
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Prints the script defining the command in a shell, add to your profile e.g.
+    /// `eval "$(shortcut init bash)"`.
+    Init {
+        /// One of bash, sh, zsh, fish or powershell.
+        shell: String,
+    },
+    /// Rolls back the profiles of a shell to the backup made the last time setup or uninstall
+    /// changed them.
+    RestoreProfile {
+        /// Shell whose profiles are restored, one of bash, sh, zsh, fish, nu, cmd or powershell.
+        shell: String,
+    },
+    /// Exports all the shortcuts, to be imported with `shortcut import <FILE>`.
+    Export {
+        /// Format of the exported shortcuts.
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// File to write the shortcuts to, by default they are written to stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Imports shortcuts from a file written by `shortcut export`, or the most used directories
+    /// from another directory jumping tool adding a shortcut named after each directory.
+    Import {
+        /// File to import the shortcuts from.
+        #[arg(required_unless_present = "from")]
+        file: Option<PathBuf>,
+        /// Format of the file, by default guessed from the file extension.
+        #[arg(long, value_enum, requires = "file")]
+        format: Option<Format>,
+        /// What to do with shortcuts from the file whose key is already used for a different
+        /// directory.
+        #[arg(long, value_enum, default_value_t = OnConflict::Skip, requires = "file")]
+        on_conflict: OnConflict,
+        /// Tool to import the directories from.
+        #[arg(long, value_enum, conflicts_with = "file")]
+        from: Option<ImportFrom>,
+        /// Location of the database, by default the location used by the tool.
+        #[arg(long, requires = "from")]
+        database: Option<PathBuf>,
+        /// Only import the given number of highest ranked directories.
+        #[arg(long, requires = "from")]
+        top: Option<usize>,
+        /// Show the shortcuts that would be added without adding them.
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Get the target directory given a key, if there is not shortcut for the given key,
     /// then the key will be returned.
+    /// Keys are matched exactly first, then by unique prefix and finally by a fuzzy match
+    /// against the keys and target directory names.
+    /// Anything after the first path component is a sub-path inside the target directory,
+    /// e.g. `repo/src/bin`.
     Get {
         /// Key.
         key: String,
     },
+    /// Interactively picks a shortcut and prints its target directory, type to filter the
+    /// shortcuts, use the arrow keys to select one and Enter to pick it or Esc to cancel.
+    /// After one-time setup you can do: $ {command}
+    Pick,
+    /// Records a visit to a directory, called by the shell hooks installed during setup.
+    /// When no shortcut matches the key given to `get`, the most frequently and recently visited
+    /// directory whose name contains the key is used.
+    #[command(hide = true)]
+    Visit {
+        /// Visited directory.
+        dir: PathBuf,
+    },
+    /// Prints the keys starting with the given prefix, or the directories inside the target after
+    /// `<KEY>/`, called by the shell completion functions installed during setup.
+    #[command(hide = true)]
+    Complete {
+        /// Partially typed key.
+        #[arg(default_value = "", allow_hyphen_values = true)]
+        prefix: String,
+    },
+    /// Prints the completion script of the `shortcut` binary itself for the given shell.
+    Completions {
+        /// Shell to print the completion script for.
+        shell: CompletionShell,
+    },
+}
+
+/// Machines a shortcut is used on, to have different targets for the same key on each machine
+/// sharing the config.
+#[derive(ClapArgs, Debug)]
+struct ScopeArgs {
+    /// Only use the shortcut on the given host, `--host` alone means the current host.
+    #[arg(long, num_args = 0..=1, require_equals = true, value_name = "HOST")]
+    host: Option<Option<String>>,
+    /// Only use the shortcut on the given operating system (linux, macos, windows...), `--os`
+    /// alone means the current one.
+    #[arg(long, num_args = 0..=1, require_equals = true, value_name = "OS")]
+    os: Option<Option<String>>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ImportFrom {
+    Zoxide,
+    Autojump,
+    Z,
+    Fasd,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ListOutput {
+    /// Human readable table.
+    Table,
+    /// JSON object with the command, path location and shortcuts.
+    Json,
+    /// Tab separated `command`, `path_location` and `shortcut` lines, with escaped values. Shortcut
+    /// lines have the key, target, source, host, OS and whether the shortcut is active.
+    Tsv,
+    /// Null terminated command, path location and then the key, target, source, host, OS and
+    /// active fields of every shortcut.
+    NullSeparated,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Json,
+    Csv,
+    BashAliases,
+    Cdpath,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OnConflict {
+    /// Keep the existing shortcut.
+    Skip,
+    /// Replace the directory of the existing shortcut.
+    Overwrite,
+    /// Add the shortcut with a numeric suffix in its key.
+    Rename,
+}
+
+impl From<ScopeArgs> for Scope {
+    fn from(value: ScopeArgs) -> Self {
+        let machine = Machine::current();
+        Scope {
+            host: value.host.map(|host| host.unwrap_or(machine.host)),
+            os: value.os.map(|os| os.unwrap_or(machine.os)),
+        }
+    }
+}
+
+impl From<ListOutput> for ListFormat {
+    fn from(value: ListOutput) -> Self {
+        match value {
+            ListOutput::Table => ListFormat::Table,
+            ListOutput::Json => ListFormat::Json,
+            ListOutput::Tsv => ListFormat::Tsv,
+            ListOutput::NullSeparated => ListFormat::NullSeparated,
+        }
+    }
+}
+
+impl From<Format> for FileFormat {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Json => FileFormat::Json,
+            Format::Csv => FileFormat::Csv,
+            Format::BashAliases => FileFormat::BashAliases,
+            Format::Cdpath => FileFormat::Cdpath,
+        }
+    }
+}
+
+impl From<OnConflict> for ConflictPolicy {
+    fn from(value: OnConflict) -> Self {
+        match value {
+            OnConflict::Skip => ConflictPolicy::Skip,
+            OnConflict::Overwrite => ConflictPolicy::Overwrite,
+            OnConflict::Rename => ConflictPolicy::Rename,
+        }
+    }
+}
+
+impl From<ImportFrom> for ImportSource {
+    fn from(value: ImportFrom) -> Self {
+        match value {
+            ImportFrom::Zoxide => ImportSource::Zoxide,
+            ImportFrom::Autojump => ImportSource::Autojump,
+            ImportFrom::Z => ImportSource::Z,
+            ImportFrom::Fasd => ImportSource::Fasd,
+        }
+    }
 }
 
 fn main() -> ExitCode {
@@ -64,16 +301,86 @@ fn main() -> ExitCode {
         Command::Setup {
             command,
             path_location,
-        } => shortcut::setup(command, path_location),
-        Command::Remove { key } => shortcut::remove(key),
-        Command::Add { key, target } => shortcut::add(key, target),
-        Command::List {} => shortcut::list(),
+            shell,
+            exclude,
+            dry_run,
+            force,
+            init,
+        } => {
+            let selection = ShellSelection {
+                only: shell,
+                exclude,
+            };
+            let registry = ShellRegistry::default();
+            let options = SetupOptions {
+                force,
+                init,
+                ..SetupOptions::default()
+            };
+            shortcut::setup(
+                &registry,
+                &selection,
+                command,
+                path_location,
+                &options,
+                dry_run,
+            )
+        }
+        Command::Uninstall {
+            remove_config,
+            force,
+        } => shortcut::uninstall(&ShellRegistry::default(), remove_config, force),
+        Command::Remove { key, scope } => shortcut::remove(key, scope.into()),
+        Command::Add {
+            key,
+            target,
+            literal,
+            scope,
+        } => shortcut::add(key, target, literal, scope.into()),
+        Command::List { format } => shortcut::list(format.into()),
+        Command::Doctor { fix } => shortcut::doctor(&ShellRegistry::default(), fix),
+        Command::Init { shell } => shortcut::init(&ShellRegistry::default(), &shell),
+        Command::RestoreProfile { shell } => {
+            shortcut::restore_profile(&ShellRegistry::default(), &shell)
+        }
+        Command::Export { format, output } => shortcut::export(format.into(), output),
+        Command::Import {
+            from: Some(from),
+            database,
+            top,
+            dry_run,
+            ..
+        } => shortcut::import_history(from.into(), database, top, dry_run),
+        Command::Import {
+            file,
+            format,
+            on_conflict,
+            dry_run,
+            ..
+        } => {
+            let file = file.expect("clap requires the file when --from is not given");
+            shortcut::import_file(
+                file,
+                format.map(FileFormat::from),
+                on_conflict.into(),
+                dry_run,
+            )
+        }
         Command::Get { key } => shortcut::get(key),
+        Command::Pick => shortcut::pick(),
+        Command::Visit { dir } => shortcut::visit(dir),
+        Command::Complete { prefix } => shortcut::complete(prefix),
+        Command::Completions { shell } => {
+            let mut command = Args::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+            Ok(())
+        }
     };
 
-    if let Err(err_msg) = result {
-        eprintln!("{}", err_msg.red());
-        return ExitCode::FAILURE;
+    if let Err(err) = result {
+        eprintln!("{}", err.to_string().red());
+        return ExitCode::from(err.exit_code());
     }
     ExitCode::SUCCESS
 }
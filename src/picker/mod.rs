@@ -0,0 +1,249 @@
+use {
+    crate::{
+        config::{Config, Machine, ShortcutKV, fuzzy_score},
+        error::Error,
+    },
+    crossterm::{
+        cursor,
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute, queue,
+        style::{Print, PrintStyledContent, Stylize},
+        terminal::{self, ClearType},
+    },
+    std::{
+        io::{self, IsTerminal, Write},
+        path::Path,
+    },
+};
+
+/// Rows used by the query line and the preview line.
+const RESERVED_ROWS: u16 = 2;
+
+/// Lets the user pick a shortcut interactively, typing filters the shortcuts by fuzzy matching
+/// the keys and target directory names. The picker is drawn on stderr so stdout can be captured
+/// by the shell scripts. Only the shortcuts used on the current machine are listed. Returns `None`
+/// if the user cancels.
+pub fn pick(config: &Config) -> Result<Option<&ShortcutKV>, Error> {
+    if config.shortcuts.is_empty() {
+        return Ok(None);
+    }
+    let mut stderr = io::stderr();
+    if !stderr.is_terminal() {
+        return Err(Error::Terminal(io::Error::other(
+            "stderr is not a terminal",
+        )));
+    }
+    let mut picker = Picker::new(config.active_shortcuts(&Machine::current()));
+    terminal::enable_raw_mode().map_err(Error::Terminal)?;
+    let result = execute!(stderr, terminal::EnterAlternateScreen)
+        .and_then(|_| run(&mut picker, &mut stderr));
+    let restored = execute!(stderr, terminal::LeaveAlternateScreen, cursor::Show)
+        .and_then(|_| terminal::disable_raw_mode());
+    let picked = result.map_err(Error::Terminal)?;
+    restored.map_err(Error::Terminal)?;
+    Ok(picked)
+}
+
+fn run<'a>(picker: &mut Picker<'a>, out: &mut impl Write) -> io::Result<Option<&'a ShortcutKV>> {
+    loop {
+        let (width, height) = match terminal::size()? {
+            // Some pseudo terminals report no size, assume the classic one.
+            (0, _) | (_, 0) => (80, 24),
+            size => size,
+        };
+        picker.render(out, width, height)?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match picker.handle_key(key) {
+                Action::Continue => (),
+                Action::Pick => return Ok(picker.selection()),
+                Action::Cancel => return Ok(None),
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Pick,
+    Cancel,
+}
+
+/// State of the picker: the typed query, the shortcuts matching it, best match first, and the
+/// selected one.
+struct Picker<'a> {
+    shortcuts: Vec<&'a ShortcutKV>,
+    query: String,
+    matches: Vec<&'a ShortcutKV>,
+    selected: usize,
+}
+
+impl<'a> Picker<'a> {
+    fn new(shortcuts: Vec<&'a ShortcutKV>) -> Self {
+        let mut picker = Picker {
+            shortcuts,
+            query: String::new(),
+            matches: vec![],
+            selected: 0,
+        };
+        picker.update_matches();
+        picker
+    }
+
+    fn selection(&self) -> Option<&'a ShortcutKV> {
+        self.matches.get(self.selected).copied()
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, &'a ShortcutKV)> = self
+            .shortcuts
+            .iter()
+            .copied()
+            .filter_map(|shortcut| {
+                let target_name = Path::new(&shortcut.value)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let score = [
+                    fuzzy_score(&self.query, &shortcut.key),
+                    fuzzy_score(&self.query, &target_name),
+                ]
+                .into_iter()
+                .flatten()
+                .max()?;
+                Some((score, shortcut))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.key.cmp(&b.1.key)));
+        self.matches = scored.into_iter().map(|(_, shortcut)| shortcut).collect();
+        self.selected = 0;
+    }
+
+    fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn move_down(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter if self.selection().is_some() => return Action::Pick,
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Char('c') if ctrl => return Action::Cancel,
+            KeyCode::Up => self.move_up(),
+            KeyCode::Char('p') if ctrl => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Char('n') if ctrl => self.move_down(),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => (),
+        }
+        Action::Continue
+    }
+
+    fn render(&self, out: &mut impl Write, width: u16, height: u16) -> io::Result<()> {
+        let width = width as usize;
+        let visible = height.saturating_sub(RESERVED_ROWS).max(1) as usize;
+        let offset = (self.selected + 1).saturating_sub(visible);
+        queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(ClearType::All),
+            Print(truncate(&format!("> {}", self.query), width)),
+        )?;
+        let rows = self.matches.iter().enumerate().skip(offset).take(visible);
+        for (row, (index, shortcut)) in rows.enumerate() {
+            let line = truncate(&format!("  {} -> {}", shortcut.key, shortcut.value), width);
+            queue!(out, cursor::MoveTo(0, row as u16 + 1))?;
+            if index == self.selected {
+                queue!(out, PrintStyledContent(line.reverse()))?;
+            } else {
+                queue!(out, Print(line))?;
+            }
+        }
+        let preview = match self.selection().map(|shortcut| shortcut.target()) {
+            Some(Ok(target)) if target.is_dir() => target.display().to_string(),
+            Some(Ok(target)) => format!("{} (not found)", target.display()),
+            Some(Err(err)) => err.to_string(),
+            None => "No matching shortcuts".to_string(),
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, visible as u16 + 1),
+            PrintStyledContent(truncate(&preview, width).dim()),
+            cursor::MoveTo((2 + self.query.chars().count()).min(width) as u16, 0),
+            cursor::Show,
+        )?;
+        out.flush()
+    }
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcuts(pairs: &[(&str, &str)]) -> Vec<ShortcutKV> {
+        pairs
+            .iter()
+            .map(|(key, value)| ShortcutKV::new(key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn keys(picker: &Picker) -> Vec<String> {
+        picker.matches.iter().map(|x| x.key.clone()).collect()
+    }
+
+    fn type_keys(picker: &mut Picker, codes: &[KeyCode]) -> Action {
+        let mut action = Action::Continue;
+        for code in codes {
+            action = picker.handle_key(KeyEvent::new(*code, KeyModifiers::NONE));
+        }
+        action
+    }
+
+    #[test]
+    fn test_picker() {
+        let shortcuts = shortcuts(&[
+            ("repo", "/home/user/code/repositories"),
+            ("dl", "/home/user/Downloads"),
+            ("docs", "/home/user/Documents"),
+        ]);
+        let mut picker = Picker::new(shortcuts.iter().collect());
+        assert_eq!(vec!["dl", "docs", "repo"], keys(&picker));
+
+        let action = type_keys(&mut picker, &[KeyCode::Char('d')]);
+        assert_eq!(Action::Continue, action);
+        assert_eq!(vec!["dl", "docs"], keys(&picker));
+
+        type_keys(&mut picker, &[KeyCode::Down, KeyCode::Down, KeyCode::Up]);
+        assert_eq!("dl", picker.selection().unwrap().key);
+        type_keys(&mut picker, &[KeyCode::Down]);
+        assert_eq!(Action::Pick, type_keys(&mut picker, &[KeyCode::Enter]));
+        assert_eq!("docs", picker.selection().unwrap().key);
+
+        type_keys(&mut picker, &[KeyCode::Char('x')]);
+        assert!(picker.selection().is_none());
+        assert_eq!(Action::Continue, type_keys(&mut picker, &[KeyCode::Enter]));
+        type_keys(&mut picker, &[KeyCode::Backspace]);
+        assert_eq!(2, picker.matches.len());
+        assert_eq!(Action::Cancel, type_keys(&mut picker, &[KeyCode::Esc]));
+    }
+}
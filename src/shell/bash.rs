@@ -1,27 +1,26 @@
 use {
-    super::common::{Shell, replace_file_content},
-    crate::config::Config,
-    crate::fs,
-    std::path::Path,
-    which::{Error as WhichError, which},
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_profile, find_executable,
+        get_script_lines, plan_profile, unsetup_profile,
+    },
+    crate::{config::Config, error::Error, fs},
+    std::path::PathBuf,
 };
 
 pub struct Bash {}
 
 const NAME: &str = "Bash";
 const BASH_FUNCTION_FILE: &str = include_str!("./script/bash.sh");
+const BASH_INIT_LINE: &str = r#"eval "$(shortcut init bash)""#;
 
 impl Bash {
-    pub fn new() -> Result<Option<Bash>, String> {
-        match which("bash") {
-            Ok(location) => {
-                let instance = Bash {};
-                println!("{} found at {}", instance.name(), location.display());
-                Ok(Some(instance))
-            }
-            Err(WhichError::CannotFindBinaryPath) => Ok(None),
-            Err(msg) => Err(format!("Error finding {}: {}", NAME, msg).to_string()),
-        }
+    pub fn new() -> Result<Option<Bash>, Error> {
+        Ok(find_executable(NAME, "bash")?.map(|_| Bash {}))
+    }
+
+    /// Bash with its profile at `~/.bashrc`, whether it is installed or not.
+    pub fn locate() -> Result<Bash, Error> {
+        Ok(Bash {})
     }
 }
 
@@ -30,38 +29,42 @@ impl Shell for Bash {
         NAME
     }
 
-    fn try_configure(&self, config: &Config) -> Result<(), String> {
-        let bashrc_dir = std::path::PathBuf::from("~/.bashrc");
+    fn try_plan(&self, config: &Config, options: &SetupOptions) -> Result<Vec<PlannedFile>, Error> {
+        let bashrc_dir = PathBuf::from("~/.bashrc");
         let bashrc_dir = fs::to_absolute_path(&bashrc_dir)?;
-        let function = get_bash_function(&config);
-        setup_bash(&config, &bashrc_dir, &function)?;
-        Ok(())
+        let function = get_bash_function(config);
+        Ok(vec![plan_profile(
+            &bashrc_dir,
+            &function,
+            BASH_INIT_LINE,
+            options,
+        )?])
+    }
+
+    fn try_unconfigure(
+        &self,
+        _config: &Config,
+        options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        let bashrc_dir = fs::to_absolute_path(&PathBuf::from("~/.bashrc"))?;
+        unsetup_profile(&bashrc_dir, options)
+    }
+
+    fn profiles(&self) -> Result<Vec<PathBuf>, Error> {
+        Ok(vec![fs::to_absolute_path(&PathBuf::from("~/.bashrc"))?])
     }
-}
 
-fn setup_bash(config: &Config, profile: &Path, function: &Vec<String>) -> Result<(), String> {
-    let existing_bashrc_content = fs::read_lines(&profile)?;
-    match existing_bashrc_content {
-        None => {
-            fs::ensure_file_parent_dir(&profile)?;
-            fs::write_lines(&profile, function)?;
-        }
-        Some(existing_content) => {
-            let function: Vec<String> = get_bash_function(config);
-            let new_content = replace_file_content(existing_content, &function);
-            fs::write_lines(&profile, &new_content)?;
-        }
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        let bashrc_dir = fs::to_absolute_path(&PathBuf::from("~/.bashrc"))?;
+        Ok(vec![check_profile(
+            &bashrc_dir,
+            &get_bash_function(config),
+            BASH_INIT_LINE,
+        )?])
     }
-    Ok(())
 }
 
-fn get_bash_function(config: &Config) -> Vec<String> {
-    let mut new_content: Vec<String> = BASH_FUNCTION_FILE
-        .to_owned()
-        .split("\n")
-        .filter(|x| x.trim().len() > 0)
-        .map(|s| s.to_owned())
-        .collect();
-    new_content[3] = format!("function {} {{", config.command).to_string();
-    new_content
+/// Script defining the command, printed by `shortcut init bash`.
+pub fn get_bash_function(config: &Config) -> Vec<String> {
+    get_script_lines(BASH_FUNCTION_FILE, &config.command)
 }
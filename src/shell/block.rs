@@ -0,0 +1,182 @@
+use {crate::error::Error, std::path::Path};
+
+/// Start marker written by versions of shortcut before blocks were versioned, still recognized.
+const LEGACY_START_MARKER: &str = "# ---------- shortcut start ----------";
+const START_MARKER_PREFIX: &str = "# ---------- shortcut start";
+const MARKER_SUFFIX: &str = "----------";
+pub const END_MARKER: &str = "# ---------- shortcut end ----------";
+/// Version of the block layout, written in the start marker.
+const BLOCK_VERSION: u32 = 2;
+
+/// A block of lines written by setup into a profile, from its start marker to its end marker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the start marker line.
+    pub fr: usize,
+    /// Index of the end marker line.
+    pub to: usize,
+    /// Hash of the lines between the markers when they were written, `None` for legacy blocks.
+    hash: Option<u64>,
+}
+
+impl Block {
+    /// Whether the lines between the markers were changed since setup wrote them. Edits to
+    /// legacy blocks can not be detected.
+    pub fn is_edited(&self, content: &[String]) -> bool {
+        self.hash
+            .is_some_and(|hash| hash != hash_lines(&content[self.fr + 1..self.to]))
+    }
+}
+
+/// Wraps the lines of a script between the markers, the start marker has the version of the
+/// block and the hash of the lines so edits by hand can be detected.
+pub fn managed_block(lines: &[String]) -> Vec<String> {
+    let start_marker = format!(
+        "{} v{} fnv1a:{:016x} {}",
+        START_MARKER_PREFIX,
+        BLOCK_VERSION,
+        hash_lines(lines),
+        MARKER_SUFFIX
+    );
+    let mut block = Vec::with_capacity(lines.len() + 2);
+    block.push(start_marker);
+    block.extend_from_slice(lines);
+    block.push(END_MARKER.to_string());
+    block
+}
+
+/// FNV-1a hash of the lines joined with new lines.
+fn hash_lines(lines: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, line) in lines.iter().enumerate() {
+        let separator: &[u8] = if i == 0 { b"" } else { b"\n" };
+        for byte in separator.iter().chain(line.as_bytes()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// Returns `Some` with the hash in the marker if the line is a start marker of any version.
+fn parse_start_marker(line: &str) -> Option<Option<u64>> {
+    let line = line.trim();
+    if line == LEGACY_START_MARKER {
+        return Some(None);
+    }
+    let fields = line
+        .strip_prefix(START_MARKER_PREFIX)?
+        .strip_suffix(MARKER_SUFFIX)?;
+    if !fields.is_empty() && !fields.starts_with(' ') {
+        return None;
+    }
+    let hash = fields
+        .split_whitespace()
+        .find_map(|x| x.strip_prefix("fnv1a:"))
+        .and_then(|x| u64::from_str_radix(x, 16).ok());
+    Some(hash)
+}
+
+/// Finds every shortcut block in the content of `profile`. Fails if a block has no end marker, as
+/// there is no telling where it was meant to end.
+pub fn find_blocks(profile: &Path, content: &[String]) -> Result<Vec<Block>, Error> {
+    let mut blocks = vec![];
+    let mut start: Option<(usize, Option<u64>)> = None;
+    for (i, line) in content.iter().enumerate() {
+        let start_hash = parse_start_marker(line);
+        match (start, start_hash) {
+            (Some((fr, _)), Some(_)) => return Err(unterminated(profile, fr)),
+            (None, Some(hash)) => start = Some((i, hash)),
+            (Some((fr, hash)), None) if line.trim() == END_MARKER => {
+                blocks.push(Block { fr, to: i, hash });
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    match start {
+        Some((fr, _)) => Err(unterminated(profile, fr)),
+        None => Ok(blocks),
+    }
+}
+
+fn unterminated(profile: &Path, fr: usize) -> Error {
+    Error::EditedProfile {
+        path: profile.to_path_buf(),
+        reason: format!(
+            "the shortcut block starting at line {} has no end marker",
+            fr + 1
+        ),
+    }
+}
+
+/// Replaces the first of the blocks with `new_block` and removes the others, `new_block` is
+/// appended if there are no blocks.
+pub fn replace_blocks(content: Vec<String>, blocks: &[Block], new_block: &[String]) -> Vec<String> {
+    let mut updated_content = remove_blocks(content, blocks.get(1..).unwrap_or(&[]));
+    match blocks.first() {
+        Some(block) => {
+            updated_content.splice(block.fr..=block.to, new_block.iter().cloned());
+        }
+        None => updated_content.extend_from_slice(new_block),
+    }
+    updated_content
+}
+
+/// Removes the lines of every block, markers included.
+pub fn remove_blocks(content: Vec<String>, blocks: &[Block]) -> Vec<String> {
+    let mut updated_content = content;
+    for block in blocks.iter().rev() {
+        updated_content.drain(block.fr..=block.to);
+    }
+    updated_content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_blocks() {
+        let profile = Path::new(".bashrc");
+        let block = managed_block(&lines(&["a", "b"]));
+        assert_eq!(4, block.len());
+        assert!(block[0].starts_with("# ---------- shortcut start v2 fnv1a:"));
+
+        let mut content = lines(&["0", LEGACY_START_MARKER, "x", END_MARKER, "1"]);
+        content.extend(block.clone());
+        content.push("2".to_string());
+        let blocks = find_blocks(profile, &content).unwrap();
+        assert_eq!(
+            vec![(1, 3), (5, 8)],
+            blocks.iter().map(|x| (x.fr, x.to)).collect::<Vec<_>>()
+        );
+        assert!(!blocks[0].is_edited(&content));
+        assert!(!blocks[1].is_edited(&content));
+
+        let new_block = managed_block(&lines(&["c"]));
+        let mut expected = lines(&["0"]);
+        expected.extend(new_block.clone());
+        expected.extend(lines(&["1", "2"]));
+        assert_eq!(
+            expected,
+            replace_blocks(content.clone(), &blocks, &new_block)
+        );
+        assert_eq!(
+            lines(&["0", "1", "2"]),
+            remove_blocks(content.clone(), &blocks)
+        );
+
+        content[6] = "edited".to_string();
+        assert!(find_blocks(profile, &content).unwrap()[1].is_edited(&content));
+
+        let unterminated = lines(&["0", LEGACY_START_MARKER, "a"]);
+        assert!(find_blocks(profile, &unterminated).is_err());
+        let nested = lines(&[LEGACY_START_MARKER, "a", LEGACY_START_MARKER, END_MARKER]);
+        assert!(find_blocks(profile, &nested).is_err());
+    }
+}
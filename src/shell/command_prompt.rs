@@ -1,9 +1,11 @@
 use {
-    super::common::Shell,
-    crate::{config::Config, fs},
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_file, find_executable,
+        unsetup_file,
+    },
+    crate::{config::Config, error::Error},
     colored::Colorize,
-    std::path::Path,
-    which::{Error as WhichError, which},
+    std::path::{Path, PathBuf},
 };
 
 pub struct CommandPrompt {}
@@ -12,16 +14,14 @@ const NAME: &str = "Command Prompt (CMD)";
 const BAT_FILE_CONTENT: &str = include_str!("./script/script.bat");
 
 impl CommandPrompt {
-    pub fn new() -> Result<Option<CommandPrompt>, String> {
-        match which("cmd") {
-            Ok(location) => {
-                let instance = CommandPrompt {};
-                println!("{} found at {}", NAME, location.display());
-                Ok(Some(instance))
-            }
-            Err(WhichError::CannotFindBinaryPath) => Ok(None),
-            Err(err) => Err(format!("Error finding {}: {}", NAME, err).to_string()),
-        }
+    pub fn new() -> Result<Option<CommandPrompt>, Error> {
+        Ok(find_executable(NAME, "cmd")?.map(|_| CommandPrompt {}))
+    }
+
+    /// Command Prompt with its script in the PATH location of the config, whether it is
+    /// installed or not.
+    pub fn locate() -> Result<CommandPrompt, Error> {
+        Ok(CommandPrompt {})
     }
 }
 
@@ -30,7 +30,11 @@ impl Shell for CommandPrompt {
         NAME
     }
 
-    fn try_configure(&self, config: &Config) -> Result<(), String> {
+    fn try_plan(
+        &self,
+        config: &Config,
+        _options: &SetupOptions,
+    ) -> Result<Vec<PlannedFile>, Error> {
         match &config.path_location {
             None => {
                 let message = format!(
@@ -38,13 +42,41 @@ impl Shell for CommandPrompt {
                     self.name(),
                 );
                 println!("{}", message.yellow());
-                Ok(())
+                Ok(vec![])
             }
             Some(path_dir) => {
-                let file_name = config.command.to_string() + ".bat";
-                let file_path = Path::new(path_dir).join(file_name);
-                fs::write_str(&file_path, BAT_FILE_CONTENT)
+                let lines: Vec<String> = BAT_FILE_CONTENT.lines().map(String::from).collect();
+                Ok(vec![PlannedFile::from_lines(
+                    &get_bat_file(config, path_dir),
+                    &lines,
+                )])
+            }
+        }
+    }
+
+    fn try_unconfigure(
+        &self,
+        config: &Config,
+        _options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        match &config.path_location {
+            None => Ok(vec![]),
+            Some(path_dir) => unsetup_file(&get_bat_file(config, path_dir)),
+        }
+    }
+
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        match &config.path_location {
+            None => Ok(vec![]),
+            Some(path_dir) => {
+                let lines: Vec<String> = BAT_FILE_CONTENT.lines().map(String::from).collect();
+                Ok(vec![check_file(&get_bat_file(config, path_dir), &lines)?])
             }
         }
     }
 }
+
+fn get_bat_file(config: &Config, path_dir: &str) -> PathBuf {
+    let file_name = config.command.to_string() + ".bat";
+    Path::new(path_dir).join(file_name)
+}
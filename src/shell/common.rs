@@ -1,78 +1,505 @@
-use crate::config::Config;
+use {
+    super::block::{find_blocks, managed_block, remove_blocks, replace_blocks},
+    crate::{config::Config, error::Error, fs},
+    colored::Colorize,
+    similar::TextDiff,
+    std::{
+        fmt,
+        path::{Path, PathBuf},
+    },
+    which::{Error as WhichError, which},
+};
+
+/// Something that was removed while undoing the setup of a shell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Removed {
+    /// The shortcut block was removed from the given profile.
+    ProfileBlock(PathBuf),
+    /// The given file created during setup was deleted.
+    File(PathBuf),
+}
+
+/// State of a file written during setup, as found by `shortcut doctor`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SetupStatus {
+    /// The file has the script that setup would write with the current config.
+    UpToDate(PathBuf),
+    /// The file has a script from another version of shortcut, another command or edited by hand.
+    Outdated(PathBuf),
+    /// The file does not exist or has no shortcut block.
+    Missing(PathBuf),
+    /// The shortcut block in the file was edited by hand.
+    Edited(PathBuf),
+    /// The file has more than one shortcut block.
+    Duplicated(PathBuf),
+}
+
+/// A file written during setup and the whole content it is written with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub lines: Vec<String>,
+    /// Format of the existing file, kept when it is written.
+    pub format: fs::TextFormat,
+    /// Whether the file is a profile of the user, backed up before it is changed.
+    pub backup: bool,
+    /// Whether writing the file overwrites a shortcut block edited by hand.
+    pub edited: bool,
+    /// Something the user should know before the file is written, e.g. that duplicate shortcut
+    /// blocks are merged.
+    pub warning: Option<String>,
+}
+
+/// How setup writes the files of a shell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SetupOptions {
+    /// Overwrite shortcut blocks edited by hand.
+    pub force: bool,
+    /// Write to the profiles a line loading the script printed by `shortcut init` instead of the
+    /// script itself, in the shells that support it.
+    pub init: bool,
+    /// Write the profiles that already have a shortcut block the way they are, with the line
+    /// loading the script or with the script itself, whatever `init` is.
+    pub keep_mode: bool,
+}
 
 pub trait Shell {
     fn name(&self) -> &'static str;
-    fn try_configure(&self, config: &Config) -> Result<(), String>;
+    /// Computes the files `try_configure` writes and their new content, without writing them.
+    fn try_plan(&self, config: &Config, options: &SetupOptions) -> Result<Vec<PlannedFile>, Error>;
+    /// Removes what `try_configure` wrote. Shortcut blocks edited by hand are only removed with
+    /// `options.force`.
+    fn try_unconfigure(
+        &self,
+        config: &Config,
+        options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error>;
+    /// Checks whether the files written by `try_configure` are still there and up to date.
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error>;
+    /// Profiles of the user that `try_configure` writes a shortcut block to, backed up before
+    /// they are changed. None by default, for shells whose setup only writes files of its own.
+    fn profiles(&self) -> Result<Vec<PathBuf>, Error> {
+        Ok(vec![])
+    }
 
-    fn configure(&self, config: &Config) {
+    /// Writes the planned files, unless one of them has a shortcut block edited by hand and
+    /// `options.force` is false.
+    fn try_configure(&self, config: &Config, options: &SetupOptions) -> Result<(), Error> {
+        let files = self.try_plan(config, options)?;
+        if let Some(file) = files.iter().find(|x| x.edited && !options.force) {
+            return Err(Error::EditedProfile {
+                path: file.path.clone(),
+                reason: "the shortcut block was edited by hand, use --force to overwrite it"
+                    .to_string(),
+            });
+        }
+        for file in files {
+            if let Some(warning) = &file.warning {
+                println!("{}", warning.yellow());
+            }
+            file.write()?;
+        }
+        Ok(())
+    }
+
+    /// `try_configure`, printing how it went. The error is returned after it is printed.
+    fn configure(&self, config: &Config, options: &SetupOptions) -> Result<(), Error> {
         println!("Setting up {}", self.name());
-        match self.try_configure(config) {
+        match self.try_configure(config, options) {
             Ok(()) => println!("Successfully set up {}", self.name()),
-            Err(msg) => println!("Erring setting up {}: {}", self.name(), msg),
+            Err(err) => {
+                let message = format!("Error setting up {}: {}", self.name(), err);
+                println!("{}", message.red());
+                return Err(err);
+            }
+        };
+        Ok(())
+    }
+
+    /// `try_unconfigure`, printing what was removed. The error is returned after it is printed.
+    fn unconfigure(&self, config: &Config, options: &SetupOptions) -> Result<(), Error> {
+        println!("Uninstalling {}", self.name());
+        match self.try_unconfigure(config, options) {
+            Ok(removed) if removed.is_empty() => {
+                println!("Nothing to remove for {}", self.name())
+            }
+            Ok(removed) => {
+                for item in removed {
+                    println!("  {}", item);
+                }
+                println!("Successfully uninstalled {}", self.name());
+            }
+            Err(err) => {
+                let message = format!("Error uninstalling {}: {}", self.name(), err);
+                println!("{}", message.red());
+                return Err(err);
+            }
+        };
+        Ok(())
+    }
+}
+
+impl fmt::Display for Removed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Removed::ProfileBlock(profile) => {
+                write!(f, "Removed shortcut block from \"{}\"", profile.display())
+            }
+            Removed::File(file) => write!(f, "Deleted file \"{}\"", file.display()),
+        }
+    }
+}
+
+/// Looks for the executable `exe` of the shell `name` in PATH, printing where it was found.
+/// Returns `None` when it is not installed.
+pub fn find_executable(name: &'static str, exe: &str) -> Result<Option<PathBuf>, Error> {
+    match which(exe) {
+        Ok(location) => {
+            println!("{} found at {}", name, location.display());
+            Ok(Some(location))
+        }
+        Err(WhichError::CannotFindBinaryPath) => Ok(None),
+        Err(err) => Err(Error::Shell {
+            shell: name,
+            message: format!("Error finding executable: {}", err),
+        }),
+    }
+}
+
+/// Placeholder for the configured command name in the embedded scripts.
+pub const COMMAND_PLACEHOLDER: &str = "__SHORTCUT_COMMAND__";
+
+/// Splits an embedded script into its non-empty lines, replacing `COMMAND_PLACEHOLDER` with the
+/// given command.
+pub fn get_script_lines(script: &str, command: &str) -> Vec<String> {
+    script
+        .split('\n')
+        .filter(|x| !x.trim().is_empty())
+        .map(|s| s.replace(COMMAND_PLACEHOLDER, command))
+        .collect()
+}
+
+impl PlannedFile {
+    /// A file owned by shortcut with the given lines, each of them ending with a new line.
+    pub fn from_lines(path: &Path, lines: &[String]) -> Self {
+        PlannedFile {
+            path: path.to_path_buf(),
+            lines: lines.to_vec(),
+            format: fs::TextFormat::default(),
+            backup: false,
+            edited: false,
+            warning: None,
+        }
+    }
+
+    /// Writes the file unless it already has the planned content, backing it up first if needed.
+    pub fn write(&self) -> Result<(), Error> {
+        let content = self.format.encode(&self.lines);
+        match fs::read_bytes(&self.path)? {
+            Some(existing_content) if existing_content == content => return Ok(()),
+            Some(_) if self.backup => {
+                let backup = fs::backup_file(&self.path)?;
+                println!(
+                    "Backed up \"{}\" to \"{}\"",
+                    self.path.display(),
+                    backup.display()
+                );
+            }
+            Some(_) => (),
+            None => fs::ensure_file_parent_dir(&self.path)?,
+        }
+        fs::write_bytes(&self.path, &content)
+    }
+
+    /// Unified diff from the current content of the file to the planned one, empty if the file
+    /// already has the planned content. A file that does not exist is diffed as `/dev/null`.
+    pub fn diff(&self) -> Result<String, Error> {
+        let path = self.path.display().to_string();
+        let (old_path, old_lines) = match fs::read_text(&self.path)? {
+            Some((lines, _)) => (&path[..], lines),
+            None => ("/dev/null", vec![]),
         };
+        if old_lines == self.lines && old_path != "/dev/null" {
+            return Ok(String::new());
+        }
+        let old_content = fs::TextFormat::default().encode(&old_lines);
+        let new_content = fs::TextFormat::default().encode(&self.lines);
+        let old_content = String::from_utf8_lossy(&old_content);
+        let new_content = String::from_utf8_lossy(&new_content);
+        let diff = TextDiff::from_lines(&old_content, &new_content);
+        Ok(diff.unified_diff().header(old_path, &path).to_string())
+    }
+}
+
+impl SetupStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        matches!(self, SetupStatus::UpToDate(_))
+    }
+}
+
+impl fmt::Display for SetupStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetupStatus::UpToDate(file) => write!(f, "\"{}\" is up to date", file.display()),
+            SetupStatus::Outdated(file) => write!(f, "\"{}\" is outdated", file.display()),
+            SetupStatus::Missing(file) => {
+                write!(f, "\"{}\" has no shortcut setup", file.display())
+            }
+            SetupStatus::Edited(file) => {
+                write!(
+                    f,
+                    "\"{}\" has a shortcut block edited by hand",
+                    file.display()
+                )
+            }
+            SetupStatus::Duplicated(file) => {
+                write!(f, "\"{}\" has several shortcut blocks", file.display())
+            }
+        }
     }
 }
 
-pub fn replace_file_content(
-    existing_content: Vec<String>,
-    new_content: &Vec<String>,
-) -> Vec<String> {
-    assert!(new_content.len() > 0);
-    let fr = existing_content
-        .iter()
-        .position(|x| x.trim() == new_content[0].trim());
-    if let Some(fr) = fr {
-        let last_line = new_content
-            .last()
-            .expect("already asserted new_content.len() > 0")
-            .trim();
-        let to = existing_content
-            .iter()
-            .skip(fr)
-            .position(|x| x.trim() == last_line);
-        if let Some(to) = to {
-            let to = to + fr;
-            let mut updated_content = existing_content;
-            let suffix = updated_content[to + 1..].to_owned();
-            updated_content.truncate(fr);
-            updated_content.extend(new_content.clone());
-            updated_content.extend_from_slice(&suffix);
-            return updated_content;
+/// Computes the profile file with a shortcut block, replacing the block from a previous setup if
+/// there is one and removing any duplicate of it. The block has the `script`, or only the
+/// `init_line` loading it with `options.init`. A profile that does not exist gets only the block.
+/// The line endings, encoding and final new line of an existing profile are kept.
+pub fn plan_profile(
+    profile: &Path,
+    script: &[String],
+    init_line: &str,
+    options: &SetupOptions,
+) -> Result<PlannedFile, Error> {
+    let (existing_content, format) = fs::read_text(profile)?.unwrap_or_default();
+    let blocks = find_blocks(profile, &existing_content)?;
+    let init = match blocks.first() {
+        Some(block) if options.keep_mode => {
+            existing_content[block.fr + 1..block.to] == [init_line.to_string()]
         }
+        _ => options.init,
+    };
+    let block = if init {
+        managed_block(&[init_line.to_string()])
+    } else {
+        managed_block(script)
+    };
+    let warning = (blocks.len() > 1).then(|| {
+        format!(
+            "Found {} shortcut blocks in \"{}\", only one is kept",
+            blocks.len(),
+            profile.display()
+        )
+    });
+    let edited = blocks.iter().any(|x| x.is_edited(&existing_content));
+    Ok(PlannedFile {
+        path: profile.to_path_buf(),
+        lines: replace_blocks(existing_content, &blocks, &block),
+        format,
+        backup: true,
+        edited,
+        warning,
+    })
+}
+
+/// Removes every shortcut block from the profile file, if there is any. Fails if a block was
+/// edited by hand, unless `options.force` is set.
+pub fn unsetup_profile(profile: &Path, options: &SetupOptions) -> Result<Vec<Removed>, Error> {
+    let (existing_content, format) = match fs::read_text(profile)? {
+        Some(text) => text,
+        None => return Ok(vec![]),
+    };
+    let blocks = find_blocks(profile, &existing_content)?;
+    if blocks.is_empty() {
+        return Ok(vec![]);
+    }
+    let edited = blocks.iter().any(|x| x.is_edited(&existing_content));
+    if edited && !options.force {
+        return Err(Error::EditedProfile {
+            path: profile.to_path_buf(),
+            reason: "the shortcut block was edited by hand, use --force to remove it".to_string(),
+        });
     }
-    let mut existing_content = existing_content;
-    existing_content.extend_from_slice(new_content);
-    existing_content
+    let planned = PlannedFile {
+        path: profile.to_path_buf(),
+        lines: remove_blocks(existing_content, &blocks),
+        format,
+        backup: true,
+        edited,
+        warning: None,
+    };
+    planned.write()?;
+    Ok(vec![Removed::ProfileBlock(profile.to_path_buf())])
 }
 
+/// Deletes a file created during setup, if it exists.
+pub fn unsetup_file(file: &Path) -> Result<Vec<Removed>, Error> {
+    if fs::remove_file(file)? {
+        Ok(vec![Removed::File(file.to_path_buf())])
+    } else {
+        Ok(vec![])
+    }
+}
+
+/// Compares the block in the profile file with the block `plan_profile` would write, with either
+/// the whole `script` or the `init_line` loading it.
+pub fn check_profile(
+    profile: &Path,
+    script: &[String],
+    init_line: &str,
+) -> Result<SetupStatus, Error> {
+    let profile = profile.to_path_buf();
+    let existing_content = match fs::read_text(&profile)? {
+        Some((content, _)) => content,
+        None => return Ok(SetupStatus::Missing(profile)),
+    };
+    let status = match &find_blocks(&profile, &existing_content)?[..] {
+        [] => SetupStatus::Missing(profile),
+        [block] if block.is_edited(&existing_content) => SetupStatus::Edited(profile),
+        [block]
+            if existing_content[block.fr..=block.to] == managed_block(script)
+                || existing_content[block.fr..=block.to]
+                    == managed_block(&[init_line.to_string()]) =>
+        {
+            SetupStatus::UpToDate(profile)
+        }
+        [_] => SetupStatus::Outdated(profile),
+        _ => SetupStatus::Duplicated(profile),
+    };
+    Ok(status)
+}
+
+/// Compares a file created during setup with the lines it would be written with.
+pub fn check_file(file: &Path, lines: &[String]) -> Result<SetupStatus, Error> {
+    let file = file.to_path_buf();
+    match fs::read_lines(&file)? {
+        Some(content) if content == lines => Ok(SetupStatus::UpToDate(file)),
+        Some(_) => Ok(SetupStatus::Outdated(file)),
+        None => Ok(SetupStatus::Missing(file)),
+    }
+}
+
+/// Fixture shared by the tests of the shells.
 #[cfg(test)]
-mod test {
+pub(crate) mod testing {
     use super::*;
 
+    /// A path in the temp dir that no other test process uses.
+    pub fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("shortcut-{}-{}", name, std::process::id()))
+    }
+
+    /// A config whose command is `goto` instead of the default, so the tests see it filled in.
+    pub fn goto_config() -> Config {
+        Config {
+            version: Config::latest(),
+            path_location: None,
+            command: "goto".to_string(),
+            shortcuts: vec![],
+        }
+    }
+
+    /// Plans the setup of `goto_config` and checks that every file names the `goto` command.
+    pub fn plan_goto(shell: &dyn Shell, options: &SetupOptions) -> Vec<PlannedFile> {
+        let planned = shell.try_plan(&goto_config(), options).unwrap();
+        assert!(!planned.is_empty());
+        for file in &planned {
+            assert!(file.lines.iter().any(|x| x.contains("goto")));
+            assert!(!file.lines.iter().any(|x| x.contains(COMMAND_PLACEHOLDER)));
+        }
+        planned
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{testing::temp_path, *};
+
     #[test]
-    fn test_replace_file_content() {
-        let existing_content: Vec<String> = vec!["0", "1", "2", "X", "A", "B", "C", "D", "Y"]
-            .into_iter()
-            .map(|x| x.to_string())
-            .collect();
-        let new_content: Vec<String> = vec!["X", "a", "b", "c", "Y"]
-            .into_iter()
-            .map(|x| x.to_string())
-            .collect();
-        let expected: Vec<String> = vec!["0", "1", "2", "X", "a", "b", "c", "Y"]
-            .into_iter()
-            .map(|x| x.to_string())
-            .collect();
-        let updated_content = replace_file_content(existing_content, &new_content);
-        assert_eq!(expected, updated_content);
-
-        let existing_content: Vec<String> = vec!["X", "1", "2", "3", "Y"]
-            .into_iter()
-            .map(|x| x.to_string())
-            .collect();
-        let new_content = existing_content.clone();
-        let expected = existing_content.clone();
-        let updated_content = replace_file_content(existing_content, &new_content);
-        assert_eq!(expected, updated_content);
+    fn test_check_profile() {
+        let profile = temp_path("profile");
+        let lines = vec!["a".to_string(), "b".to_string()];
+        let status = |lines: &[String]| check_profile(&profile, lines, "init").unwrap();
+        assert_eq!(SetupStatus::Missing(profile.clone()), status(&lines));
+
+        fs::write_lines(&profile, &["echo hi".to_string()]).unwrap();
+        assert_eq!(SetupStatus::Missing(profile.clone()), status(&lines));
+
+        let plan = |lines: &[String], options: &SetupOptions| {
+            plan_profile(&profile, lines, "init", options).unwrap()
+        };
+        let default = SetupOptions::default();
+        let planned = plan(&lines, &default);
+        assert!(planned.warning.is_none());
+        planned.write().unwrap();
+        assert_eq!(SetupStatus::UpToDate(profile.clone()), status(&lines));
+
+        let mut new_lines = lines.clone();
+        new_lines[1] = "c".to_string();
+        assert_eq!(SetupStatus::Outdated(profile.clone()), status(&new_lines));
+        let options = SetupOptions {
+            init: true,
+            ..SetupOptions::default()
+        };
+        plan(&lines, &options).write().unwrap();
+        assert_eq!(SetupStatus::UpToDate(profile.clone()), status(&new_lines));
+        // Keeping the mode rewrites the init line, not the script.
+        let keep_mode = SetupOptions {
+            keep_mode: true,
+            ..SetupOptions::default()
+        };
+        assert_eq!(4, plan(&new_lines, &keep_mode).lines.len());
+        plan(&lines, &default).write().unwrap();
+        assert_eq!(5, plan(&new_lines, &keep_mode).lines.len());
+
+        let mut content = fs::read_lines(&profile).unwrap().unwrap();
+        content[2] = "edited".to_string();
+        fs::write_lines(&profile, &content).unwrap();
+        assert_eq!(SetupStatus::Edited(profile.clone()), status(&lines));
+        assert!(plan(&lines, &default).edited);
+
+        content.extend(managed_block(&lines));
+        fs::write_lines(&profile, &content).unwrap();
+        assert_eq!(SetupStatus::Duplicated(profile.clone()), status(&lines));
+        let planned = plan(&lines, &default);
+        assert_eq!(5, planned.lines.len());
+        assert_eq!(managed_block(&lines), planned.lines[1..]);
+        assert!(planned.warning.is_some());
+        assert!(plan(&lines, &default).warning.is_some());
+
+        // The edited block is only removed with force.
+        assert!(matches!(
+            unsetup_profile(&profile, &default),
+            Err(Error::EditedProfile { .. })
+        ));
+        let force = SetupOptions {
+            force: true,
+            ..SetupOptions::default()
+        };
+        assert_eq!(
+            vec![Removed::ProfileBlock(profile.clone())],
+            unsetup_profile(&profile, &force).unwrap()
+        );
+        assert_eq!(
+            vec!["echo hi".to_string()],
+            fs::read_lines(&profile).unwrap().unwrap()
+        );
+        std::fs::remove_file(&profile).expect("Temp file should be removed");
+    }
+
+    #[test]
+    fn test_planned_file_diff() {
+        let path = temp_path("plan");
+        let lines =
+            |lines: &[&str]| -> Vec<String> { lines.iter().map(|x| x.to_string()).collect() };
+        let planned = PlannedFile::from_lines(&path, &lines(&["a", "b"]));
+        let diff = planned.diff().unwrap();
+        assert!(diff.starts_with("--- /dev/null\n"));
+        assert!(diff.contains("+a\n+b\n"));
+
+        fs::write_lines(&path, &lines(&["a", "c"])).unwrap();
+        let diff = planned.diff().unwrap();
+        assert!(diff.contains("-c\n+b\n"));
+        planned.write().unwrap();
+        assert_eq!("", planned.diff().unwrap());
+        std::fs::remove_file(&path).expect("Temp file should be removed");
     }
 }
@@ -0,0 +1,159 @@
+use {
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_file, find_executable,
+        get_script_lines, unsetup_file,
+    },
+    crate::{config::Config, error::Error, fs},
+    std::{
+        ffi::OsString,
+        path::{Path, PathBuf},
+    },
+};
+
+pub struct Fish {
+    /// Fish config directory, e.g. `~/.config/fish`.
+    config_dir: PathBuf,
+}
+
+const NAME: &str = "Fish";
+const FISH_FUNCTION_FILE: &str = include_str!("./script/fish.fish");
+const FISH_COMPLETIONS_FILE: &str = include_str!("./script/fish_completions.fish");
+
+impl Fish {
+    pub fn new() -> Result<Option<Fish>, Error> {
+        match find_executable(NAME, "fish")? {
+            Some(_) => Ok(Some(Fish::locate()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fish with its files in `$XDG_CONFIG_HOME/fish`, whether it is installed or not.
+    pub fn locate() -> Result<Fish, Error> {
+        Ok(Fish {
+            config_dir: get_config_dir(std::env::var_os("XDG_CONFIG_HOME"))?,
+        })
+    }
+}
+
+impl Shell for Fish {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn try_plan(
+        &self,
+        config: &Config,
+        _options: &SetupOptions,
+    ) -> Result<Vec<PlannedFile>, Error> {
+        // Fish autoloads `functions/<name>.fish` the first time `<name>` is called, so the
+        // function gets its own file instead of a block in `config.fish`, the same goes for
+        // its completions in `completions/<name>.fish`.
+        Ok(vec![
+            plan_script(
+                &self.get_script_file("functions", config),
+                FISH_FUNCTION_FILE,
+                config,
+            ),
+            plan_script(
+                &self.get_script_file("completions", config),
+                FISH_COMPLETIONS_FILE,
+                config,
+            ),
+        ])
+    }
+
+    fn try_unconfigure(
+        &self,
+        config: &Config,
+        _options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        let mut removed = unsetup_file(&self.get_script_file("functions", config))?;
+        removed.extend(unsetup_file(&self.get_script_file("completions", config))?);
+        Ok(removed)
+    }
+
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        let function_file = self.get_script_file("functions", config);
+        let completions_file = self.get_script_file("completions", config);
+        Ok(vec![
+            check_file(
+                &function_file,
+                &get_script_lines(FISH_FUNCTION_FILE, &config.command),
+            )?,
+            check_file(
+                &completions_file,
+                &get_script_lines(FISH_COMPLETIONS_FILE, &config.command),
+            )?,
+        ])
+    }
+}
+
+/// Function and completions of the command, printed by `shortcut init fish`.
+pub fn get_fish_function(config: &Config) -> Vec<String> {
+    let mut lines = get_script_lines(FISH_FUNCTION_FILE, &config.command);
+    lines.extend(get_script_lines(FISH_COMPLETIONS_FILE, &config.command));
+    lines
+}
+
+fn plan_script(file: &Path, script: &str, config: &Config) -> PlannedFile {
+    PlannedFile::from_lines(file, &get_script_lines(script, &config.command))
+}
+
+impl Fish {
+    /// File named after the command in the given directory of the fish config, e.g. `functions`.
+    fn get_script_file(&self, dir: &str, config: &Config) -> PathBuf {
+        self.config_dir
+            .join(dir)
+            .join(format!("{}.fish", config.command))
+    }
+}
+
+/// Fish reads its config from `$XDG_CONFIG_HOME/fish`, falling back to `~/.config/fish`.
+fn get_config_dir(xdg_config_home: Option<OsString>) -> Result<PathBuf, Error> {
+    let config_home = match xdg_config_home {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from("~/.config"),
+    };
+    fs::to_absolute_path(&config_home.join("fish"))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::shell::common::testing::plan_goto};
+
+    #[test]
+    fn test_plan() {
+        let home = fs::to_absolute_path(&PathBuf::from("~")).unwrap();
+        assert_eq!(home.join(".config/fish"), get_config_dir(None).unwrap());
+        assert_eq!(
+            PathBuf::from("/xdg/fish"),
+            get_config_dir(Some("/xdg".into())).unwrap()
+        );
+
+        // Files of their own, autoloaded by the command name, instead of a block in config.fish.
+        let shell = Fish {
+            config_dir: PathBuf::from("/xdg/fish"),
+        };
+        let planned = plan_goto(&shell, &SetupOptions::default());
+        assert_eq!(
+            vec![
+                PathBuf::from("/xdg/fish/functions/goto.fish"),
+                PathBuf::from("/xdg/fish/completions/goto.fish"),
+            ],
+            planned.iter().map(|x| x.path.clone()).collect::<Vec<_>>()
+        );
+        assert!(planned.iter().all(|x| !x.backup));
+        assert!(
+            planned[0]
+                .lines
+                .iter()
+                .any(|x| x.starts_with("function goto "))
+        );
+        assert!(
+            planned[1]
+                .lines
+                .iter()
+                .any(|x| x.starts_with("complete --command goto "))
+        );
+    }
+}
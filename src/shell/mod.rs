@@ -1,9 +1,23 @@
 pub mod bash;
+mod block;
 pub mod command_prompt;
 mod common;
+pub mod fish;
+pub mod nushell;
+pub mod posix;
 pub mod power_shell;
+mod registry;
+pub mod zsh;
 
 pub use bash::Bash;
 pub use command_prompt::CommandPrompt;
-pub use common::Shell;
+pub use common::{
+    COMMAND_PLACEHOLDER, PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_file,
+    check_profile, find_executable, get_script_lines, plan_profile, unsetup_file, unsetup_profile,
+};
+pub use fish::Fish;
+pub use nushell::Nushell;
+pub use posix::Posix;
 pub use power_shell::PowerShell;
+pub use registry::{Backend, ShellRegistry, ShellSelection};
+pub use zsh::Zsh;
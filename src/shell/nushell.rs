@@ -0,0 +1,184 @@
+use {
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_file, find_executable,
+        get_script_lines, unsetup_file,
+    },
+    crate::{config::Config, error::Error},
+    directories::BaseDirs,
+    std::{ffi::OsString, path::PathBuf, process::Command},
+    which::which,
+};
+
+/// Nushell, a child process can not change its directory so the command is defined with
+/// `def --env` in a file of the autoload directory.
+pub struct Nushell {
+    autoload_dir: PathBuf,
+}
+
+const NAME: &str = "Nushell";
+const NU_EXE: &str = "nu";
+const NU_FUNCTION_FILE: &str = include_str!("./script/nushell.nu");
+const NU_SCRIPT_FILE_NAME: &str = "shortcut.nu";
+
+impl Nushell {
+    pub fn new() -> Result<Option<Nushell>, Error> {
+        match find_executable(NAME, NU_EXE)? {
+            Some(_) => Ok(Some(Nushell {
+                autoload_dir: get_autoload_dir()?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Nushell with its script in the autoload directory, whether it is installed or not. Without
+    /// `nu` in PATH the directory is the default one, in the config directory of the platform.
+    pub fn locate() -> Result<Nushell, Error> {
+        let autoload_dir = match which(NU_EXE) {
+            Ok(_) => get_autoload_dir()?,
+            Err(_) => get_default_autoload_dir(std::env::var_os("XDG_CONFIG_HOME"))?,
+        };
+        Ok(Nushell { autoload_dir })
+    }
+}
+
+impl Shell for Nushell {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn try_plan(
+        &self,
+        config: &Config,
+        _options: &SetupOptions,
+    ) -> Result<Vec<PlannedFile>, Error> {
+        let script_file = self.autoload_dir.join(NU_SCRIPT_FILE_NAME);
+        Ok(vec![PlannedFile::from_lines(
+            &script_file,
+            &get_nu_function(config),
+        )])
+    }
+
+    fn try_unconfigure(
+        &self,
+        _config: &Config,
+        _options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        unsetup_file(&self.autoload_dir.join(NU_SCRIPT_FILE_NAME))
+    }
+
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        let script_file = self.autoload_dir.join(NU_SCRIPT_FILE_NAME);
+        Ok(vec![check_file(&script_file, &get_nu_function(config))?])
+    }
+}
+
+fn get_autoload_dir() -> Result<PathBuf, Error> {
+    // Every `.nu` file in `$nu.default-config-dir/autoload` is sourced on startup, see
+    // https://www.nushell.sh/book/configuration.html#configuration-overview
+    let mut command = Command::new(NU_EXE);
+    let command = command.args([
+        "--no-config-file",
+        "--commands",
+        "$nu.default-config-dir | path join autoload",
+    ]);
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(err) => {
+            let msg = format!("Could not get Nushell autoload directory: {}", err);
+            return Err(Error::Shell {
+                shell: NAME,
+                message: msg,
+            });
+        }
+    };
+    if !output.status.success() {
+        let msg = format!(
+            "`{} --commands \"$nu.default-config-dir\"` exited with non-success status code",
+            NU_EXE
+        );
+        return Err(Error::Shell {
+            shell: NAME,
+            message: msg,
+        });
+    }
+    match String::from_utf8(output.stdout) {
+        Ok(dir) => Ok(PathBuf::from(dir.trim())),
+        Err(err) => {
+            let msg = format!("Error decoding Nushell autoload directory: {}", err);
+            Err(Error::Shell {
+                shell: NAME,
+                message: msg,
+            })
+        }
+    }
+}
+
+/// `$nu.default-config-dir/autoload` of a default Nushell install, which is in
+/// `$XDG_CONFIG_HOME` when it is set.
+fn get_default_autoload_dir(xdg_config_home: Option<OsString>) -> Result<PathBuf, Error> {
+    let config_dir = match xdg_config_home {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => BaseDirs::new()
+            .ok_or(Error::NoHomeDirectory)?
+            .config_dir()
+            .to_path_buf(),
+    };
+    Ok(config_dir.join("nushell").join("autoload"))
+}
+
+fn get_nu_function(config: &Config) -> Vec<String> {
+    get_script_lines(NU_FUNCTION_FILE, &config.command)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::shell::common::testing::{goto_config, plan_goto, temp_path},
+    };
+
+    #[test]
+    fn test_plan() {
+        let autoload_dir = temp_path("nu-autoload");
+        let shell = Nushell {
+            autoload_dir: autoload_dir.clone(),
+        };
+        let config = goto_config();
+        let script_file = autoload_dir.join("shortcut.nu");
+        // `def --env` lets the command change the directory of the calling shell.
+        let planned = plan_goto(&shell, &SetupOptions::default());
+        assert_eq!(1, planned.len());
+        assert_eq!(script_file, planned[0].path);
+        assert!(!planned[0].backup);
+        assert!(
+            planned[0]
+                .lines
+                .iter()
+                .any(|x| x.starts_with("def --env goto "))
+        );
+
+        // The autoload directory is created when the script is written.
+        assert_eq!(
+            vec![SetupStatus::Missing(script_file.clone())],
+            shell.try_check(&config).unwrap()
+        );
+        planned[0].write().unwrap();
+        assert_eq!(
+            vec![SetupStatus::UpToDate(script_file.clone())],
+            shell.try_check(&config).unwrap()
+        );
+        assert_eq!(
+            vec![Removed::File(script_file.clone())],
+            shell
+                .try_unconfigure(&config, &SetupOptions::default())
+                .unwrap()
+        );
+        assert!(!script_file.exists());
+        std::fs::remove_dir(&autoload_dir).expect("Temp dir should be removed");
+
+        assert_eq!(
+            PathBuf::from("/xdg/nushell/autoload"),
+            get_default_autoload_dir(Some("/xdg".into())).unwrap()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use {
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_profile, find_executable,
+        get_script_lines, plan_profile, unsetup_profile,
+    },
+    crate::{config::Config, error::Error, fs},
+    std::{
+        ffi::OsString,
+        path::{Path, PathBuf},
+    },
+};
+
+/// POSIX compliant shells such as `dash`, `ash` or `busybox sh`.
+pub struct Posix {
+    profile: PathBuf,
+}
+
+const NAME: &str = "POSIX shell (sh)";
+const POSIX_FUNCTION_FILE: &str = include_str!("./script/posix.sh");
+const POSIX_INIT_LINE: &str = r#"eval "$(shortcut init sh)""#;
+
+impl Posix {
+    pub fn new() -> Result<Option<Posix>, Error> {
+        let env = std::env::var_os("ENV").filter(|x| !x.is_empty());
+        let env_set = env.is_some();
+        let profile = get_profile(env)?;
+        if !is_used(env_set, &profile)? {
+            return Ok(None);
+        }
+        Ok(find_executable(NAME, "sh")?.map(|_| Posix { profile }))
+    }
+
+    /// POSIX shells with their profile at `$ENV` or `~/.profile`, whether they are used or not.
+    pub fn locate() -> Result<Posix, Error> {
+        Ok(Posix {
+            profile: get_profile(std::env::var_os("ENV"))?,
+        })
+    }
+}
+
+impl Shell for Posix {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn try_plan(&self, config: &Config, options: &SetupOptions) -> Result<Vec<PlannedFile>, Error> {
+        let function = get_posix_function(config);
+        Ok(vec![plan_profile(
+            &self.profile,
+            &function,
+            POSIX_INIT_LINE,
+            options,
+        )?])
+    }
+
+    fn try_unconfigure(
+        &self,
+        _config: &Config,
+        options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        unsetup_profile(&self.profile, options)
+    }
+
+    fn profiles(&self) -> Result<Vec<PathBuf>, Error> {
+        Ok(vec![self.profile.clone()])
+    }
+
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        Ok(vec![check_profile(
+            &self.profile,
+            &get_posix_function(config),
+            POSIX_INIT_LINE,
+        )?])
+    }
+}
+
+/// Interactive POSIX shells source the file named by `$ENV`, login shells source `~/.profile`.
+fn get_profile(env: Option<OsString>) -> Result<PathBuf, Error> {
+    let profile = match env {
+        Some(env) if !env.is_empty() => PathBuf::from(env),
+        _ => PathBuf::from("~/.profile"),
+    };
+    fs::to_absolute_path(&profile)
+}
+
+/// `sh` is on every Unix, so it is only set up when `$ENV` names its profile or ~/.profile exists.
+/// A ~/.profile sourcing ~/.bashrc, the default on Debian and Ubuntu, is left alone: bash login
+/// shells read it too, and a block appended to it would override the bash command.
+fn is_used(env_set: bool, profile: &Path) -> Result<bool, Error> {
+    if env_set {
+        return Ok(true);
+    }
+    let sources_bashrc = |line: &String| {
+        let line = line.trim_start();
+        !line.starts_with('#') && line.contains(".bashrc")
+    };
+    match fs::read_lines(profile)? {
+        Some(lines) => Ok(!lines.iter().any(sources_bashrc)),
+        None => Ok(false),
+    }
+}
+
+/// Script defining the command, printed by `shortcut init sh`.
+pub fn get_posix_function(config: &Config) -> Vec<String> {
+    get_script_lines(POSIX_FUNCTION_FILE, &config.command)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::shell::common::testing::{goto_config, plan_goto, temp_path},
+    };
+
+    #[test]
+    fn test_plan() {
+        let home = fs::to_absolute_path(&PathBuf::from("~")).unwrap();
+        assert_eq!(home.join(".profile"), get_profile(None).unwrap());
+        assert_eq!(home.join(".profile"), get_profile(Some("".into())).unwrap());
+        assert_eq!(
+            home.join(".shrc"),
+            get_profile(Some("~/.shrc".into())).unwrap()
+        );
+
+        let profile = temp_path("posix");
+        let shell = Posix {
+            profile: profile.clone(),
+        };
+        // sh has no `pushd`, so the function keeps its own directory stack.
+        let planned = plan_goto(&shell, &SetupOptions::default());
+        assert_eq!(1, planned.len());
+        assert_eq!(profile, planned[0].path);
+        assert!(planned[0].backup);
+        assert!(planned[0].lines.contains(&"goto() {".to_string()));
+        assert!(!planned[0].lines.iter().any(|x| x.contains("pushd")));
+
+        let options = SetupOptions {
+            init: true,
+            ..SetupOptions::default()
+        };
+        let planned = shell.try_plan(&goto_config(), &options).unwrap();
+        assert_eq!(POSIX_INIT_LINE, planned[0].lines[1]);
+    }
+
+    #[test]
+    fn test_is_used() {
+        let profile = temp_path("posix-used");
+        assert!(!is_used(false, &profile).unwrap());
+        assert!(is_used(true, &profile).unwrap());
+
+        let write = |lines: &[&str]| {
+            let lines: Vec<String> = lines.iter().map(|x| x.to_string()).collect();
+            fs::write_lines(&profile, &lines).unwrap();
+        };
+        write(&["export EDITOR=vi", "# . \"$HOME/.bashrc\""]);
+        assert!(is_used(false, &profile).unwrap());
+        write(&[
+            "if [ -f \"$HOME/.bashrc\" ]; then",
+            "    . \"$HOME/.bashrc\"",
+            "fi",
+        ]);
+        assert!(!is_used(false, &profile).unwrap());
+        assert!(is_used(true, &profile).unwrap());
+        std::fs::remove_file(&profile).expect("Temp file should be removed");
+    }
+}
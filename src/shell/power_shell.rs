@@ -1,13 +1,19 @@
 use {
-    super::common::{Shell, replace_file_content},
-    crate::{config::Config, fs},
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_profile, find_executable,
+        get_script_lines, plan_profile, unsetup_profile,
+    },
+    crate::{config::Config, error::Error},
+    directories::UserDirs,
     std::{path::PathBuf, process::Command},
-    which::which,
 };
 
+const NAME: &str = "PowerShell";
 const POWER_SHELL_EXE: &str = "pwsh";
 const WINDOWS_POWER_SHELL_EXE: &str = "powershell";
 const PS1_FUNCTION_FILE: &str = include_str!("./script/script.ps1");
+const PS1_PROFILE_FILE_NAME: &str = "Microsoft.PowerShell_profile.ps1";
+const PS1_INIT_LINE: &str = "Invoke-Expression (& shortcut init powershell | Out-String)";
 
 /// PowerShell, includes both:
 /// - Windows PowerShell: powershell.exe
@@ -17,7 +23,7 @@ pub struct PowerShell {
 }
 
 impl PowerShell {
-    pub fn new() -> Result<Option<PowerShell>, String> {
+    pub fn new() -> Result<Option<PowerShell>, Error> {
         let mut profile_locations: Vec<String> = Vec::new();
         for exec in [POWER_SHELL_EXE, WINDOWS_POWER_SHELL_EXE] {
             match get_power_shell_default_profile(exec) {
@@ -26,35 +32,77 @@ impl PowerShell {
                 Ok(None) => (),
             };
         }
-        if profile_locations.len() > 0 {
+        if !profile_locations.is_empty() {
             return Ok(Some(PowerShell { profile_locations }));
         }
         Ok(None)
     }
+
+    /// PowerShell with the profiles given by `$PROFILE` if it is installed, and the default
+    /// profiles of the platform if it is not.
+    pub fn locate() -> Result<PowerShell, Error> {
+        match PowerShell::new()? {
+            Some(shell) => Ok(shell),
+            None => Ok(PowerShell {
+                profile_locations: get_default_profiles()?,
+            }),
+        }
+    }
 }
 
 impl Shell for PowerShell {
     fn name(&self) -> &'static str {
-        "PowerShell"
+        NAME
+    }
+
+    fn try_plan(&self, config: &Config, options: &SetupOptions) -> Result<Vec<PlannedFile>, Error> {
+        let function = get_power_shell_function(config);
+        self.profile_locations
+            .iter()
+            .map(|profile_location| {
+                plan_profile(
+                    &PathBuf::from(profile_location),
+                    &function,
+                    PS1_INIT_LINE,
+                    options,
+                )
+            })
+            .collect()
     }
 
-    fn try_configure(&self, config: &Config) -> Result<(), String> {
+    fn try_unconfigure(
+        &self,
+        _config: &Config,
+        options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        let mut removed = Vec::new();
         for profile_location in &self.profile_locations {
-            let function = get_power_shell_function(&config);
-            setup_power_shell_profile(&config, &profile_location, &function)?;
+            removed.extend(unsetup_profile(&PathBuf::from(profile_location), options)?);
         }
-        Ok(())
+        Ok(removed)
+    }
+
+    fn profiles(&self) -> Result<Vec<PathBuf>, Error> {
+        Ok(self.profile_locations.iter().map(PathBuf::from).collect())
+    }
+
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        let function = get_power_shell_function(config);
+        self.profile_locations
+            .iter()
+            .map(|profile_location| {
+                check_profile(&PathBuf::from(profile_location), &function, PS1_INIT_LINE)
+            })
+            .collect()
     }
 }
 
-fn get_power_shell_default_profile(exec: &str) -> Result<Option<String>, String> {
+fn get_power_shell_default_profile(exec: &str) -> Result<Option<String>, Error> {
     // See the following docs for more info about PowerPhell profiles.
     // https://learn.microsoft.com/en-us/powershell/module/microsoft.powershell.core/about/about_profiles?view=powershell-7.5
-    let power_shell_exe = match which(exec) {
-        Ok(exe_location) => exe_location,
-        Err(_) => return Ok(None),
-    };
-    println!("{} found at {}", POWER_SHELL_EXE, power_shell_exe.display());
+    if find_executable(NAME, exec)?.is_none() {
+        return Ok(None);
+    }
     let mut command = Command::new(exec);
     // https://learn.microsoft.com/en-us/powershell/module/microsoft.powershell.core/about/about_powershell_exe?view=powershell-5.1
     let command = command.args(["-Command", "Write-Output $PROFILE"]);
@@ -62,7 +110,10 @@ fn get_power_shell_default_profile(exec: &str) -> Result<Option<String>, String>
         Ok(output) => output,
         Err(err) => {
             let msg = format!("Could not get PowerShell default profile: {}", err);
-            return Err(msg.to_string());
+            return Err(Error::Shell {
+                shell: NAME,
+                message: msg,
+            });
         }
     };
     if !output.status.success() {
@@ -71,7 +122,10 @@ fn get_power_shell_default_profile(exec: &str) -> Result<Option<String>, String>
                   exited with non-success status code"#,
             exec
         );
-        return Err(msg.to_string());
+        return Err(Error::Shell {
+            shell: NAME,
+            message: msg,
+        });
     }
     // This will probably fail in some older versions of powershell, need some logic to parse
     // different types of encoding.
@@ -83,41 +137,43 @@ fn get_power_shell_default_profile(exec: &str) -> Result<Option<String>, String>
                       updating PowerShell might help: {}"#,
                 err
             );
-            return Err(msg.to_string());
+            return Err(Error::Shell {
+                shell: NAME,
+                message: msg,
+            });
         }
     };
     let profile_location = power_shell_output.trim().to_string();
     Ok(Some(profile_location))
 }
 
-fn setup_power_shell_profile(
-    config: &Config,
-    profile: &str,
-    function: &Vec<String>,
-) -> Result<(), String> {
-    let profile = PathBuf::from(profile);
-    let existing_profile_content = fs::read_lines(&profile)?;
-    match existing_profile_content {
-        None => {
-            fs::ensure_file_parent_dir(&profile)?;
-            fs::write_lines(&profile, function)?;
-        }
-        Some(existing_content) => {
-            let function: Vec<String> = get_power_shell_function(config);
-            let new_content = replace_file_content(existing_content, &function);
-            fs::write_lines(&profile, &new_content)?;
-        }
-    }
-    Ok(())
+/// `$PROFILE` of a default install of PowerShell and Windows PowerShell.
+fn get_default_profiles() -> Result<Vec<String>, Error> {
+    let user_dirs = UserDirs::new().ok_or(Error::NoHomeDirectory)?;
+    let dirs = if cfg!(windows) {
+        let documents = match user_dirs.document_dir() {
+            Some(dir) => dir.to_path_buf(),
+            None => user_dirs.home_dir().join("Documents"),
+        };
+        vec![
+            documents.join("PowerShell"),
+            documents.join("WindowsPowerShell"),
+        ]
+    } else {
+        vec![user_dirs.home_dir().join(".config").join("powershell")]
+    };
+    let profiles = dirs
+        .into_iter()
+        .map(|dir| {
+            dir.join(PS1_PROFILE_FILE_NAME)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    Ok(profiles)
 }
 
-fn get_power_shell_function(config: &Config) -> Vec<String> {
-    let mut new_content: Vec<String> = PS1_FUNCTION_FILE
-        .to_owned()
-        .split("\n")
-        .filter(|x| x.trim().len() > 0)
-        .map(|s| s.to_owned())
-        .collect();
-    new_content[3] = format!("function {} {{", config.command).to_string();
-    new_content
+/// Script defining the command, printed by `shortcut init powershell`.
+pub fn get_power_shell_function(config: &Config) -> Vec<String> {
+    get_script_lines(PS1_FUNCTION_FILE, &config.command)
 }
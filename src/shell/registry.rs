@@ -0,0 +1,205 @@
+use {
+    super::{
+        Bash, CommandPrompt, Fish, Nushell, Posix, PowerShell, Shell, Zsh, bash, fish, posix,
+        power_shell, zsh,
+    },
+    crate::{config::Config, error::Error},
+};
+
+/// Looks for an installation of a shell, `None` if it is not installed.
+type FindShell = Box<dyn Fn() -> Result<Option<Box<dyn Shell>>, Error>>;
+/// The shell with its files where they are by default, whether it is installed or not.
+type LocateShell = Box<dyn Fn() -> Result<Box<dyn Shell>, Error>>;
+
+/// A shell backend known to a `ShellRegistry`.
+pub struct Backend {
+    /// Name used to select the backend in the command line, e.g. `bash`.
+    pub id: &'static str,
+    find: FindShell,
+    locate: Option<LocateShell>,
+    /// Script defining the command printed by `shortcut init`, `None` if the shell can not load
+    /// it at startup.
+    pub init: Option<fn(&Config) -> Vec<String>>,
+}
+
+impl Backend {
+    /// Looks for an installation of the shell, `None` if it is not installed.
+    pub fn find(&self) -> Result<Option<Box<dyn Shell>>, Error> {
+        (self.find)()
+    }
+
+    /// The shell with its files where they are by default even if it is not installed, so
+    /// uninstall and restore-profile still find them after the shell was removed. Falls back to
+    /// `find` for backends without `with_locate`.
+    pub fn locate(&self) -> Result<Option<Box<dyn Shell>>, Error> {
+        match &self.locate {
+            Some(locate) => Ok(Some(locate()?)),
+            None => self.find(),
+        }
+    }
+
+    pub fn with_locate<S, F>(&mut self, locate: F) -> &mut Self
+    where
+        S: Shell + 'static,
+        F: Fn() -> Result<S, Error> + 'static,
+    {
+        self.locate = Some(Box::new(move || Ok(Box::new(locate()?) as Box<dyn Shell>)));
+        self
+    }
+
+    pub fn with_init(&mut self, init: fn(&Config) -> Vec<String>) -> &mut Self {
+        self.init = Some(init);
+        self
+    }
+}
+
+/// The shell backends setup, uninstall and doctor work with. `ShellRegistry::default()` has every
+/// built-in backend, library users can register their own.
+pub struct ShellRegistry {
+    backends: Vec<Backend>,
+}
+
+/// Which backends of a registry to use: the ones in `only`, or all of them if it is empty, except
+/// the ones in `exclude`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShellSelection {
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ShellRegistry {
+    /// A registry without backends.
+    pub fn empty() -> Self {
+        ShellRegistry { backends: vec![] }
+    }
+
+    /// Adds a backend found with `find`, usually the `new` function of the shell. A backend with
+    /// the same `id` is replaced.
+    pub fn register<S, F>(&mut self, id: &'static str, find: F) -> &mut Backend
+    where
+        S: Shell + 'static,
+        F: Fn() -> Result<Option<S>, Error> + 'static,
+    {
+        let find: FindShell =
+            Box::new(move || Ok(find()?.map(|shell| Box::new(shell) as Box<dyn Shell>)));
+        let backend = Backend {
+            id,
+            find,
+            locate: None,
+            init: None,
+        };
+        match self.backends.iter().position(|x| x.id == id) {
+            Some(index) => {
+                self.backends[index] = backend;
+                &mut self.backends[index]
+            }
+            None => {
+                self.backends.push(backend);
+                self.backends.last_mut().expect("A backend was just pushed")
+            }
+        }
+    }
+
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.backends.iter().map(|x| x.id).collect()
+    }
+
+    pub fn backends(&self) -> &[Backend] {
+        &self.backends
+    }
+
+    pub fn get(&self, id: &str) -> Result<&Backend, Error> {
+        match self.backends.iter().find(|x| x.id == id) {
+            Some(backend) => Ok(backend),
+            None => Err(Error::UnknownShell {
+                name: id.to_string(),
+                known: self.ids(),
+            }),
+        }
+    }
+
+    /// Whether the backend `id` is selected, fails if `selection` names an unknown backend.
+    pub fn is_selected(&self, id: &str, selection: &ShellSelection) -> Result<bool, Error> {
+        for name in selection.only.iter().chain(&selection.exclude) {
+            self.get(name)?;
+        }
+        let included = selection.only.is_empty() || selection.only.iter().any(|x| x == id);
+        Ok(included && !selection.exclude.iter().any(|x| x == id))
+    }
+}
+
+impl Default for ShellRegistry {
+    fn default() -> Self {
+        let mut registry = ShellRegistry::empty();
+        registry
+            .register("bash", Bash::new)
+            .with_locate(Bash::locate)
+            .with_init(bash::get_bash_function);
+        registry
+            .register("sh", Posix::new)
+            .with_locate(Posix::locate)
+            .with_init(posix::get_posix_function);
+        registry
+            .register("zsh", Zsh::new)
+            .with_locate(Zsh::locate)
+            .with_init(zsh::get_zsh_function);
+        registry
+            .register("fish", Fish::new)
+            .with_locate(Fish::locate)
+            .with_init(fish::get_fish_function);
+        registry
+            .register("nu", Nushell::new)
+            .with_locate(Nushell::locate);
+        registry
+            .register("cmd", CommandPrompt::new)
+            .with_locate(CommandPrompt::locate);
+        registry
+            .register("powershell", PowerShell::new)
+            .with_locate(PowerShell::locate)
+            .with_init(power_shell::get_power_shell_function);
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(only: &[&str], exclude: &[&str]) -> ShellSelection {
+        ShellSelection {
+            only: only.iter().map(|x| x.to_string()).collect(),
+            exclude: exclude.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    fn selected(registry: &ShellRegistry, selection: &ShellSelection) -> Vec<&'static str> {
+        registry
+            .ids()
+            .into_iter()
+            .filter(|id| registry.is_selected(id, selection).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_selection() {
+        let registry = ShellRegistry::default();
+        assert_eq!(registry.ids(), selected(&registry, &selection(&[], &[])));
+        assert_eq!(
+            vec!["bash", "zsh"],
+            selected(&registry, &selection(&["zsh", "bash"], &[]))
+        );
+        assert_eq!(
+            vec!["bash", "sh", "zsh", "fish", "nu"],
+            selected(&registry, &selection(&[], &["cmd", "powershell"]))
+        );
+        assert!(matches!(
+            registry.is_selected("bash", &selection(&["tcsh"], &[])),
+            Err(Error::UnknownShell { name, .. }) if name == "tcsh"
+        ));
+
+        let mut registry = ShellRegistry::empty();
+        registry.register("bash", Bash::new);
+        registry.register("bash", Zsh::new);
+        assert_eq!(vec!["bash"], registry.ids());
+    }
+}
@@ -1,7 +1,6 @@
-# ---------- shortcut start ----------
 # This code was auto-generated by `$ shortcut setup`.
 # See `$ shortcut -- help` for more info.
-function s() {
+function __SHORTCUT_COMMAND__ {
     if [ "$1" == "-a" ]; then
         shortcut add "$2" "$3"
     elif [ "$1" == "-r" ]; then
@@ -10,8 +9,29 @@ function s() {
         shortcut list
     elif [ "$1" == "-b" ]; then
         popd
+    elif [ -z "$1" ]; then
+        local dir
+        dir="$(shortcut pick)" && [ -n "$dir" ] && pushd "$dir"
     else
-        pushd $(shortcut get "$1")
-    fi 
+        pushd "$(shortcut get "$1")"
+    fi
 }
-# ---------- shortcut end ----------
+function __shortcut_visit {
+    if [ "$__shortcut_last_dir" != "$PWD" ]; then
+        __shortcut_last_dir="$PWD"
+        shortcut visit "$PWD" > /dev/null 2>&1
+    fi
+}
+function __shortcut_complete {
+    if [ "$COMP_CWORD" -eq 1 ]; then
+        local IFS=$'\n'
+        COMPREPLY=($(shortcut complete "${COMP_WORDS[1]}" 2> /dev/null))
+        if [[ "${COMPREPLY[0]}" == */ ]]; then
+            compopt -o nospace
+        fi
+    fi
+}
+complete -F __shortcut_complete __SHORTCUT_COMMAND__
+if [[ ";${PROMPT_COMMAND:-};" != *";__shortcut_visit;"* ]]; then
+    PROMPT_COMMAND="__shortcut_visit${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+fi
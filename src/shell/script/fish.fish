@@ -0,0 +1,17 @@
+# This code was auto-generated by `$ shortcut setup`.
+# See `$ shortcut -- help` for more info.
+function __SHORTCUT_COMMAND__ --description 'Change directory using shortcuts'
+    if test "$argv[1]" = "-a"
+        shortcut add "$argv[2]" "$argv[3]"
+    else if test "$argv[1]" = "-r"
+        shortcut remove "$argv[2]"
+    else if test "$argv[1]" = "-l"
+        shortcut list
+    else if test "$argv[1]" = "-b"
+        popd
+    else if test -z "$argv[1]"
+        set -l dir (shortcut pick); and test -n "$dir"; and pushd "$dir"
+    else
+        pushd (shortcut get "$argv[1]")
+    end
+end
@@ -0,0 +1,3 @@
+# This code was auto-generated by `$ shortcut setup`.
+# See `$ shortcut -- help` for more info.
+complete --command __SHORTCUT_COMMAND__ --no-files --condition __fish_is_first_arg --arguments '(shortcut complete (commandline --current-token) 2> /dev/null)'
@@ -0,0 +1,31 @@
+# This code was auto-generated by `$ shortcut setup`.
+# See `$ shortcut -- help` for more info.
+__SHORTCUT_COMMAND__() {
+    _shortcut_nl=$(printf '\n_')
+    _shortcut_nl=${_shortcut_nl%_}
+    if [ "$1" = "-a" ]; then
+        shortcut add "$2" "$3"
+    elif [ "$1" = "-r" ]; then
+        shortcut remove "$2"
+    elif [ "$1" = "-l" ]; then
+        shortcut list
+    elif [ "$1" = "-b" ]; then
+        if [ -z "$_shortcut_stack" ]; then
+            echo "shortcut: directory stack empty" >&2
+            return 1
+        fi
+        _shortcut_dir=${_shortcut_stack%%"$_shortcut_nl"*}
+        _shortcut_stack=${_shortcut_stack#*"$_shortcut_nl"}
+        cd "$_shortcut_dir" || return
+    else
+        if [ -z "$1" ]; then
+            _shortcut_dir=$(shortcut pick) || return
+            [ -n "$_shortcut_dir" ] || return 0
+        else
+            _shortcut_dir=$(shortcut get "$1") || return
+        fi
+        _shortcut_prev=$PWD
+        cd "$_shortcut_dir" || return
+        _shortcut_stack=$_shortcut_prev$_shortcut_nl$_shortcut_stack
+    fi
+}
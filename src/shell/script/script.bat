@@ -9,6 +9,8 @@ IF "%1"=="-a" (
     shortcut list
 ) ELSE IF "%1"=="-b" (
     POPD
+) ELSE IF "%~1"=="" (
+    FOR /F "usebackq delims=" %%G in (`shortcut pick`) DO (PUSHD "%%G")
 ) ELSE (
     FOR /F "usebackq delims=" %%G in (`shortcut get "%~1"`) DO (PUSHD "%%G")
 )
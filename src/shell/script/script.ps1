@@ -1,11 +1,9 @@
-# ---------- shortcut start ----------
 # This code was auto-generated by `$ shortcut setup`.
 # See `$ shortcut -- help` for more info.
-function s {
+function __SHORTCUT_COMMAND__ {
     param (
         [Parameter(
             Position = 0,
-            Mandatory = $true,
             HelpMessage="Enter <KEY> or one of the following commands: `"+`", `"-`", `"*`".")]
         [string]$p1,
         [Parameter(Position = 1)]
@@ -19,11 +17,32 @@ function s {
         shortcut remove $p2
     } elseif ($p1 -eq "-l") {
         shortcut list
-    } elseif ($p1 -eq "-l") {
+    } elseif ($p1 -eq "-b") {
         Pop-Location
+    } elseif (-not $p1) {
+        $dir = shortcut pick
+        if ($dir) {
+            Push-Location "$dir"
+        }
     } else {
         $dir = shortcut get $p1
         Push-Location "$dir"
     }
 }
-# ---------- shortcut end ----------
+Register-ArgumentCompleter -CommandName __SHORTCUT_COMMAND__ -ParameterName p1 -ScriptBlock {
+    param($commandName, $parameterName, $wordToComplete, $commandAst, $fakeBoundParameters)
+    shortcut complete "$wordToComplete" 2> $null | ForEach-Object {
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }
+}
+if (-not (Test-Path Variable:Global:__ShortcutPrompt)) {
+    $Global:__ShortcutPrompt = $Function:prompt
+    function Global:prompt {
+        $location = Get-Location
+        if ($location.Provider.Name -eq "FileSystem" -and $location.ProviderPath -ne $Global:__ShortcutLastLocation) {
+            $Global:__ShortcutLastLocation = $location.ProviderPath
+            shortcut visit $location.ProviderPath *> $null
+        }
+        & $Global:__ShortcutPrompt
+    }
+}
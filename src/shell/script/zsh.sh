@@ -0,0 +1,34 @@
+# This code was auto-generated by `$ shortcut setup`.
+# See `$ shortcut -- help` for more info.
+function __SHORTCUT_COMMAND__() {
+    if [[ "$1" == "-a" ]]; then
+        shortcut add "$2" "$3"
+    elif [[ "$1" == "-r" ]]; then
+        shortcut remove "$2"
+    elif [[ "$1" == "-l" ]]; then
+        shortcut list
+    elif [[ "$1" == "-b" ]]; then
+        popd
+    elif [[ -z "$1" ]]; then
+        local dir
+        dir="$(shortcut pick)" && [[ -n "$dir" ]] && pushd "$dir"
+    else
+        pushd "$(shortcut get "$1")"
+    fi
+}
+function __shortcut_visit() {
+    shortcut visit "$PWD" > /dev/null 2>&1
+}
+function __shortcut_complete() {
+    if (( CURRENT == 2 )); then
+        local -a completions
+        completions=(${(f)"$(shortcut complete "$words[2]" 2> /dev/null)"})
+        compadd -S '' -- ${(M)completions:#*/}
+        compadd -- ${completions:#*/}
+    fi
+}
+if (( $+functions[compdef] )); then
+    compdef __shortcut_complete __SHORTCUT_COMMAND__
+fi
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd __shortcut_visit
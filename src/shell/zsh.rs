@@ -0,0 +1,126 @@
+use {
+    super::common::{
+        PlannedFile, Removed, SetupOptions, SetupStatus, Shell, check_profile, find_executable,
+        get_script_lines, plan_profile, unsetup_profile,
+    },
+    crate::{config::Config, error::Error, fs},
+    std::{ffi::OsString, path::PathBuf},
+};
+
+pub struct Zsh {
+    zshrc: PathBuf,
+}
+
+const NAME: &str = "Zsh";
+const ZSH_FUNCTION_FILE: &str = include_str!("./script/zsh.sh");
+const ZSH_INIT_LINE: &str = r#"eval "$(shortcut init zsh)""#;
+
+impl Zsh {
+    pub fn new() -> Result<Option<Zsh>, Error> {
+        match find_executable(NAME, "zsh")? {
+            Some(_) => Ok(Some(Zsh::locate()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Zsh with its profile in `$ZDOTDIR`, whether it is installed or not.
+    pub fn locate() -> Result<Zsh, Error> {
+        Ok(Zsh {
+            zshrc: get_zshrc(std::env::var_os("ZDOTDIR"))?,
+        })
+    }
+}
+
+impl Shell for Zsh {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn try_plan(&self, config: &Config, options: &SetupOptions) -> Result<Vec<PlannedFile>, Error> {
+        let function = get_zsh_function(config);
+        Ok(vec![plan_profile(
+            &self.zshrc,
+            &function,
+            ZSH_INIT_LINE,
+            options,
+        )?])
+    }
+
+    fn try_unconfigure(
+        &self,
+        _config: &Config,
+        options: &SetupOptions,
+    ) -> Result<Vec<Removed>, Error> {
+        unsetup_profile(&self.zshrc, options)
+    }
+
+    fn profiles(&self) -> Result<Vec<PathBuf>, Error> {
+        Ok(vec![self.zshrc.clone()])
+    }
+
+    fn try_check(&self, config: &Config) -> Result<Vec<SetupStatus>, Error> {
+        Ok(vec![check_profile(
+            &self.zshrc,
+            &get_zsh_function(config),
+            ZSH_INIT_LINE,
+        )?])
+    }
+}
+
+/// Zsh reads its startup files from `$ZDOTDIR`, falling back to the home directory when unset.
+fn get_zshrc(zdotdir: Option<OsString>) -> Result<PathBuf, Error> {
+    let zdotdir = match zdotdir {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from("~"),
+    };
+    fs::to_absolute_path(&zdotdir.join(".zshrc"))
+}
+
+/// Script defining the command, printed by `shortcut init zsh`.
+pub fn get_zsh_function(config: &Config) -> Vec<String> {
+    get_script_lines(ZSH_FUNCTION_FILE, &config.command)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::shell::{
+            block::END_MARKER,
+            common::testing::{goto_config, plan_goto, temp_path},
+        },
+    };
+
+    #[test]
+    fn test_plan() {
+        let home = fs::to_absolute_path(&PathBuf::from("~")).unwrap();
+        assert_eq!(home.join(".zshrc"), get_zshrc(None).unwrap());
+        assert_eq!(home.join(".zshrc"), get_zshrc(Some("".into())).unwrap());
+        assert_eq!(
+            PathBuf::from("/etc/zsh/.zshrc"),
+            get_zshrc(Some("/etc/zsh".into())).unwrap()
+        );
+
+        let zshrc = temp_path("zshrc");
+        let shell = Zsh {
+            zshrc: zshrc.clone(),
+        };
+        // A block in .zshrc completing the command and recording visits on every `cd`.
+        let planned = plan_goto(&shell, &SetupOptions::default());
+        assert_eq!(1, planned.len());
+        assert_eq!(zshrc, planned[0].path);
+        assert!(planned[0].backup);
+        let lines = &planned[0].lines;
+        assert_eq!(END_MARKER, lines[lines.len() - 1]);
+        assert!(lines.contains(&"    compdef __shortcut_complete goto".to_string()));
+        assert!(lines.contains(&"add-zsh-hook chpwd __shortcut_visit".to_string()));
+
+        let options = SetupOptions {
+            init: true,
+            ..SetupOptions::default()
+        };
+        let planned = shell.try_plan(&goto_config(), &options).unwrap();
+        assert_eq!(ZSH_INIT_LINE, planned[0].lines[1]);
+        assert_eq!(3, planned[0].lines.len());
+    }
+}